@@ -1,6 +1,19 @@
-use std::{mem, num::NonZeroUsize, ptr};
-
-use super::borrow::ReusableMemoryBorrow;
+use std::{
+	alloc::Layout,
+	collections::TryReserveError,
+	convert::TryFrom,
+	hash::{Hash, Hasher},
+	mem::{self, MaybeUninit},
+	num::NonZeroUsize,
+	ptr
+};
+
+use super::{
+	borrow::{
+		BorrowTracking, ReusableMemoryBorrow, ReusableMemoryBorrowError, SplitScratch, TrackedBorrow
+	},
+	tuple::{block_offsets, list_align, list_needed_bytes, BorrowLayoutList}
+};
 
 /// `align_up(base, align)` returns the smallest greater integer than `base` aligned to `align`.
 ///
@@ -15,108 +28,125 @@ use super::borrow::ReusableMemoryBorrow;
 /// where `x = base` and `d = align`
 ///
 /// Similar code to `std::alloc::Layout::padding_needed_for`, but without the `- base`
-const fn align_up(base: usize, align: usize) -> usize {
+pub(crate) const fn align_up(base: usize, align: usize) -> usize {
 	base.wrapping_add(align.wrapping_sub(1)) & !align.wrapping_sub(1)
 }
+/// Returns the offset in bytes needed to align `ptr` to `align`.
+///
+/// Unlike `pointer::align_offset`, this never gives up, which it is allowed to do
+/// (for example under Miri). In that case, the offset is computed from the address instead.
+pub(crate) fn align_offset(ptr: *const u8, align: usize) -> usize {
+	match ptr.align_offset(align) {
+		usize::MAX => {
+			let address = ptr as usize;
+			align_up(address, align) - address
+		}
+		offset => offset
+	}
+}
+/// Byte written over the reserved memory before it is freed in debug builds.
+pub(crate) const POISON_BYTE: u8 = 0xDE;
+/// Fills the whole reserved memory of `vec` with `POISON_BYTE` in debug builds.
+///
+/// Stale borrows reading the memory after it was freed then read an obvious pattern instead of plausible data.
+pub(crate) fn poison<B>(vec: &mut Vec<B>) {
+	if cfg!(debug_assertions) {
+		// The length is always zero, so the whole capacity is unused.
+		debug_assert_eq!(vec.len(), 0);
+
+		unsafe {
+			let bytes = vec.capacity() * mem::size_of::<B>();
+			ptr::write_bytes(vec.as_mut_ptr() as *mut u8, POISON_BYTE, bytes);
+		}
+	}
+}
 macro_rules! impl_borrow_mut_X_as {
 	(
+		pub fn $layout_name: ident;
+		pub fn $padding_name: ident;
 		pub fn $capacity_name: ident;
 		pub fn $name: ident<$($gen_name: ident),+>[$count: literal];
 	) => {
-		pub fn $capacity_name<$($gen_name),+>(
+		/// Returns `(byte_offset, capacity)` of each block relative to the start of the borrowed memory.
+		pub fn $layout_name<$($gen_name),+>(
 			&self, capacity: [NonZeroUsize; $count]
-		) -> usize {
-			let align_of: [usize; $count] = [$(mem::align_of::<$gen_name>()),+];
-
-			$(
-				assert_ne!(mem::size_of::<$gen_name>(), 0);
-			)+
+		) -> [(usize, usize); $count] {
+			let layouts = <($($gen_name,)+) as BorrowLayoutList>::LAYOUTS;
+			assert!(layouts.iter().all(|&(size, _)| size != 0));
+
+			let mut layout = [(0, 0); $count];
+			let blocks = block_offsets(layouts, &capacity).zip(capacity.iter());
+			for (block, (offset, capacity)) in layout.iter_mut().zip(blocks) {
+				*block = (offset, capacity.get());
+			}
 
-			let needed_bytes = 0;
-			let counter = 0;
+			layout
+		}
 
-			$(
-				// where the block for $gen_name starts, in bytes, and the index
-				#[allow(non_snake_case)]
-				let $gen_name: (usize, usize) = (align_up(needed_bytes, mem::align_of::<$gen_name>()), counter);
-				// where the block from $gen_name ends
-				let needed_bytes = $gen_name.0 + mem::size_of::<$gen_name>() * capacity[counter].get();
+		/// Returns the number of padding bytes inserted between the blocks to align them.
+		///
+		/// This does not include the padding needed to align the start of the borrowed memory.
+		pub fn $padding_name<$($gen_name),+>(&self, capacity: [NonZeroUsize; $count]) -> usize {
+			let layouts = <($($gen_name,)+) as BorrowLayoutList>::LAYOUTS;
+			assert!(layouts.iter().all(|&(size, _)| size != 0));
 
-				#[allow(unused_variables)]
-				let counter = counter + 1;
-			)+
+			// Checked for overflow first, the blocks alone can't need more bytes.
+			let needed_bytes = list_needed_bytes(layouts, &capacity);
+			let block_bytes: usize =
+				layouts.iter().zip(capacity.iter()).map(|(&(size, _), c)| size * c.get()).sum();
 
-			// Add `align - 1` to `needed_bytes` if align of `T` is more than align of `B`.
-			let align_bump = if mem::align_of::<B>() >= mem::align_of::<T>() {
-				0
-			} else {
-				align_of[0] - 1
-			};
-			// Add `align_bump` afterwards so that $gen_name starts are correct
-			let needed_bytes = needed_bytes + align_bump;
-			let needed_length = (needed_bytes + mem::size_of::<B>() - 1) / mem::size_of::<B>();
+			needed_bytes - block_bytes
+		}
 
-			needed_length
+		#[must_use]
+		pub fn $capacity_name<$($gen_name),+>(
+			&self, capacity: [NonZeroUsize; $count]
+		) -> usize {
+			self.needed_capacity_for_tuple::<($($gen_name,)+)>(capacity)
 		}
 
+		/// Borrows the reusable memory as multiple different types at once.
+		///
+		/// Returns an error if any of the types is zero sized.
 		pub fn $name<'mem, $($gen_name),+>(
 			&'mem mut self, capacity: [NonZeroUsize; $count]
-		) ->( $(ReusableMemoryBorrow<'mem, $gen_name>),+ ) {
-			let align_of: [usize; $count] = [$(mem::align_of::<$gen_name>()),+];
-
-			$(
-				assert_ne!(mem::size_of::<$gen_name>(), 0);
-			)+
-
-			let needed_bytes = 0;
-			let counter = 0;
-
-			$(
-				// where the block for $gen_name starts, in bytes, and the index
-				#[allow(non_snake_case)]
-				let $gen_name: (usize, usize) = (align_up(needed_bytes, mem::align_of::<$gen_name>()), counter);
-				// where the block from $gen_name ends
-				let needed_bytes = $gen_name.0 + mem::size_of::<$gen_name>() * capacity[counter].get();
-
-				#[allow(unused_variables)]
-				let counter = counter + 1;
-			)+
-
-			// Add `align - 1` to `needed_bytes` if align of `T` is more than align of `B`.
-			let align_bump = if mem::align_of::<B>() >= mem::align_of::<T>() {
-				0
-			} else {
-				align_of[0] - 1
-			};
-			// Add `align_bump` afterwards so that $gen_name starts are correct
-			let needed_bytes = needed_bytes + align_bump;
-			let needed_length = (needed_bytes + mem::size_of::<B>() - 1) / mem::size_of::<B>();
-
-			// Reserve the memory
-			self.vec.reserve(needed_length);
-			let memory_ptr = self.vec.as_mut_ptr();
+		) -> Result<( $(ReusableMemoryBorrow<'mem, $gen_name>),+ ), ReusableMemoryBorrowError> {
+			self.borrow_mut_tuple_as::<($($gen_name,)+)>(capacity)
+		}
+	}
+}
 
-			// Compute the offset we need from the vec pointer to have the proper alignment.
-			let align_offset = memory_ptr.align_offset(align_of[0]);
-			if align_offset == std::usize::MAX {
-				panic!("Could not align pointer");
-			}
+/// Checks that the const capacity `N` is not zero at compile time.
+struct ConstCapacity<const N: usize>;
+impl<const N: usize> ConstCapacity<N> {
+	const CAPACITY: NonZeroUsize = match NonZeroUsize::new(N) {
+		Some(capacity) => capacity,
+		None => panic!("Capacity must not be zero")
+	};
+}
 
-			unsafe {
-				(
-					$(
-						ReusableMemoryBorrow::from_raw_parts(
-							ptr::NonNull::new_unchecked(
-								(memory_ptr.add(align_offset) as *mut u8).add($gen_name.0) as *mut $gen_name
-							),
-							capacity[$gen_name.1]
-						)
-					),+
-				)
-			}
-		}
+/// Error returned when converting a vec of zero sized `B`s into `ReusableMemory<B>`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ZeroSizedBaseError;
+impl std::fmt::Display for ZeroSizedBaseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "The base type of reusable memory must not be zero sized.")
 	}
 }
+impl std::error::Error for ZeroSizedBaseError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+/// Statistics of the reserved memory as returned by `ReusableMemory::stats`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MemoryStats {
+	/// Size of the reserved memory in bytes.
+	pub reserved_bytes: usize,
+	/// Size of `B` in bytes.
+	pub element_size: usize,
+	/// Number of `B`s the reserved memory can hold.
+	pub capacity_elements: usize
+}
 
 /// Reusable memory struct.
 ///
@@ -124,31 +154,119 @@ macro_rules! impl_borrow_mut_X_as {
 ///
 /// The generic type `B` can be used to control the alignment of the base memory, but it must not be zero sized.
 /// Using a zero sized `B` returns an error in constructor.
-#[derive(Debug, Clone)]
+///
+/// Borrows point into the heap allocation owned by this struct, which does not move when the struct
+/// itself is moved. Still, every borrow keeps the struct mutably borrowed, so it can't be moved,
+/// reallocated or dropped while a borrow is alive:
+///
+/// ```compile_fail
+/// # use reusable_memory::ReusableMemory;
+/// let mut memory: ReusableMemory = ReusableMemory::new();
+/// let borrow = memory.borrow_mut_as::<u32>(std::num::NonZeroUsize::new(1).unwrap());
+///
+/// let moved = memory;
+/// drop(borrow);
+/// ```
+///
+/// Borrows never write their length back, so the backing vec always has a length of zero
+/// no matter how much of it was used. To find out how much memory was actually used,
+/// borrow through [`borrow_mut_as_tracked`](#method.borrow_mut_as_tracked) and read
+/// [`high_water_bytes`](#method.high_water_bytes).
+#[derive(Debug)]
 pub struct ReusableMemory<B = u8> {
-	vec: Vec<B>
+	vec: Vec<B>,
+	tracking: BorrowTracking,
+	/// See `set_shrink_threshold`.
+	shrink_threshold: f32,
+	/// Number of bytes from the start of the reserved memory that are known to be zero,
+	/// see `borrow_mut_as_zeroed`.
+	known_zero_bytes: usize
 }
 impl<B> ReusableMemory<B> {
 	impl_borrow_mut_X_as!(
+		pub fn layout_for_two;
+		pub fn padding_overhead_for_two;
 		pub fn needed_capacity_for_two;
 		pub fn borrow_mut_two_as<T, U>[2];
 	);
 
 	impl_borrow_mut_X_as!(
+		pub fn layout_for_three;
+		pub fn padding_overhead_for_three;
 		pub fn needed_capacity_for_three;
 		pub fn borrow_mut_three_as<T, U, V>[3];
 	);
 
 	impl_borrow_mut_X_as!(
+		pub fn layout_for_four;
+		pub fn padding_overhead_for_four;
 		pub fn needed_capacity_for_four;
 		pub fn borrow_mut_four_as<T, U, V, W>[4];
 	);
 
 	impl_borrow_mut_X_as!(
+		pub fn layout_for_five;
+		pub fn padding_overhead_for_five;
 		pub fn needed_capacity_for_five;
 		pub fn borrow_mut_five_as<T, U, V, W, X>[5];
 	);
 
+	/// Returns the number of `B`s needed to borrow all the types in `L` at once.
+	#[must_use]
+	pub fn needed_capacity_for_tuple<L: BorrowLayoutList>(&self, capacity: L::Caps) -> usize {
+		assert!(L::LAYOUTS.iter().all(|&(size, _)| size != 0));
+
+		let align = list_align(L::LAYOUTS);
+		let needed_bytes = list_needed_bytes(L::LAYOUTS, capacity.as_ref());
+
+		// Add `align - 1` to `needed_bytes` if the alignment is more than align of `B`.
+		let align_bump = if mem::align_of::<B>() >= align { 0 } else { align - 1 };
+		let needed_bytes = needed_bytes.checked_add(align_bump).expect("capacity overflow");
+
+		needed_bytes.div_ceil(mem::size_of::<B>())
+	}
+
+	/// Borrows the reusable memory as all the types in the tuple `L` at once.
+	///
+	/// This works the same as the `borrow_mut_X_as` methods, but for any tuple implementing `BorrowLayoutList`:
+	///
+	/// ```
+	/// # use std::num::NonZeroUsize;
+	/// let mut memory: reusable_memory::ReusableMemory = reusable_memory::ReusableMemory::new();
+	/// let (mut borrow_t, mut borrow_u) = memory.borrow_mut_tuple_as::<(u64, u8)>(
+	/// 	[NonZeroUsize::new(1).unwrap(), NonZeroUsize::new(2).unwrap()]
+	/// ).unwrap();
+	/// borrow_t.push(1).unwrap();
+	/// borrow_u.push(2).unwrap();
+	/// ```
+	///
+	/// Returns an error if any of the types is zero sized.
+	pub fn borrow_mut_tuple_as<'mem, L: BorrowLayoutList + 'mem>(
+		&'mem mut self, capacity: L::Caps
+	) -> Result<L::Borrows<'mem>, ReusableMemoryBorrowError> {
+		if L::LAYOUTS.iter().any(|&(size, _)| size == 0) {
+			return Err(ReusableMemoryBorrowError::ZeroSizedType)
+		}
+
+		let needed_length = self.needed_capacity_for_tuple::<L>(capacity);
+
+		// Reserve the memory
+		self.vec.reserve(needed_length);
+		let memory_ptr = self.aligned_ptr(list_align(L::LAYOUTS));
+
+		unsafe { Ok(L::borrows_from_raw(memory_ptr, capacity)) }
+	}
+
+	/// Wraps `vec`, which must be empty, with default settings.
+	const fn from_empty_vec(vec: Vec<B>) -> Self {
+		ReusableMemory {
+			vec,
+			tracking: BorrowTracking::new(),
+			shrink_threshold: 0.0,
+			known_zero_bytes: 0
+		}
+	}
+
 	/// Creates new reusable memory without checking the size of `B`.
 	///
 	/// Can be used in const context.
@@ -156,7 +274,7 @@ impl<B> ReusableMemory<B> {
 	/// ### Safety
 	///
 	/// * `std::mem::size_of::<B>()` must not be zero.
-	pub const unsafe fn new_unchecked() -> Self { ReusableMemory { vec: Vec::new() } }
+	pub const unsafe fn new_unchecked() -> Self { Self::from_empty_vec(Vec::new()) }
 
 	/// Panics if `size_of::<B>() == 0`
 	pub fn new() -> Self { Self::with_capacity(0) }
@@ -167,9 +285,32 @@ impl<B> ReusableMemory<B> {
 	pub fn with_capacity(len: usize) -> Self {
 		assert_ne!(mem::size_of::<B>(), 0);
 
-		ReusableMemory { vec: Vec::with_capacity(len) }
+		Self::from_empty_vec(Vec::with_capacity(len))
+	}
+
+	/// Counted in the capacity of `B`.
+	///
+	/// Unlike [`with_capacity`](#method.with_capacity), returns an error instead of aborting
+	/// when `len` `B`s overflow or can't be allocated.
+	///
+	/// Panics if `size_of::<B>() == 0`
+	pub fn try_with_capacity(len: usize) -> Result<Self, TryReserveError> {
+		assert_ne!(mem::size_of::<B>(), 0);
+
+		let mut vec = Vec::new();
+		vec.try_reserve_exact(len)?;
+
+		Ok(Self::from_empty_vec(vec))
 	}
 
+	/// Returns the number of `B`s needed to borrow `count` of `T`s.
+	///
+	/// ```compile_fail
+	/// #![deny(unused_must_use)]
+	/// let memory: reusable_memory::ReusableMemory = reusable_memory::ReusableMemory::new();
+	/// memory.needed_capacity_for::<u32>(std::num::NonZeroUsize::new(1).unwrap());
+	/// ```
+	#[must_use]
 	pub fn needed_capacity_for<T>(&self, count: NonZeroUsize) -> usize {
 		assert_ne!(mem::size_of::<T>(), 0);
 
@@ -178,14 +319,146 @@ impl<B> ReusableMemory<B> {
 			if mem::align_of::<B>() >= mem::align_of::<T>() { 0 } else { mem::align_of::<T>() - 1 };
 
 		// Needed length in bytes.
-		let needed_length = {
-			let needed_bytes = mem::size_of::<T>() * count.get() + align_bump;
+		let needed_bytes = mem::size_of::<T>()
+			.checked_mul(count.get())
+			.and_then(|bytes| bytes.checked_add(align_bump))
+			.expect("capacity overflow");
 
-			// Needed length divided by the size of `B`, or the number of `B`s needed rounded up.
-			(needed_bytes + mem::size_of::<B>() - 1) / mem::size_of::<B>()
-		};
+		// Needed length divided by the size of `B`, or the number of `B`s needed rounded up.
+		needed_bytes.div_ceil(mem::size_of::<B>())
+	}
+
+	/// Returns the highest capacity of `T`s that [`borrow_mut_as`](#method.borrow_mut_as)
+	/// can borrow from the reserved memory without reallocating.
+	///
+	/// This accounts for the worst case alignment bump,
+	/// same as [`needed_capacity_for`](#method.needed_capacity_for).
+	///
+	/// With a shrink threshold set by [`set_shrink_threshold`](#method.set_shrink_threshold),
+	/// `borrow_mut_as` may still shrink the reserved memory for a borrow much smaller than this.
+	pub fn available_for<T>(&self) -> usize {
+		assert_ne!(mem::size_of::<T>(), 0);
+
+		let align_bump =
+			if mem::align_of::<B>() >= mem::align_of::<T>() { 0 } else { mem::align_of::<T>() - 1 };
+
+		(self.vec.capacity() * mem::size_of::<B>()).saturating_sub(align_bump) / mem::size_of::<T>()
+	}
+
+	/// Sets the ratio of needed to reserved memory under which [`borrow_mut_as`](#method.borrow_mut_as)
+	/// shrinks the reserved memory to what it needs.
+	///
+	/// This bounds the reserved memory after an occasional large borrow. The threshold is `0.0`
+	/// by default, which never shrinks. Other borrow methods never shrink.
+	///
+	/// Panics if `ratio` is not between `0.0` and `1.0`.
+	pub fn set_shrink_threshold(&mut self, ratio: f32) {
+		assert!((0.0 ..= 1.0).contains(&ratio));
+
+		self.shrink_threshold = ratio;
+	}
 
-		needed_length
+	/// Reserves memory for at least `len` `B`s in total.
+	///
+	/// Borrows that fit into the reserved memory will not reallocate.
+	pub fn reserve(&mut self, len: usize) {
+		// The memory may be reallocated.
+		self.known_zero_bytes = 0;
+		self.vec.reserve(len);
+	}
+
+	/// Reserves memory so that `capacity` of `T`s aligned to `align` can be borrowed later
+	/// without reallocating, for example through [`borrow_mut_raw`](#method.borrow_mut_raw).
+	///
+	/// The alignment used is the greater of `align` and the alignment of `T`.
+	///
+	/// Panics if `size_of::<T>() == 0`, if `align` is not a power of two
+	/// or if the needed number of bytes overflows `usize`.
+	pub fn reserve_for_aligned<T>(&mut self, capacity: NonZeroUsize, align: NonZeroUsize) {
+		assert_ne!(mem::size_of::<T>(), 0);
+		assert!(align.is_power_of_two());
+
+		let align = align.get().max(mem::align_of::<T>());
+		// Add `align - 1` to `needed_bytes` if the alignment is more than align of `B`.
+		let align_bump = if mem::align_of::<B>() >= align { 0 } else { align - 1 };
+		let needed_bytes = mem::size_of::<T>()
+			.checked_mul(capacity.get())
+			.and_then(|bytes| bytes.checked_add(align_bump))
+			.expect("capacity overflow");
+
+		self.reserve(needed_bytes.div_ceil(mem::size_of::<B>()));
+	}
+
+	/// Frees the reserved memory.
+	///
+	/// In debug builds, the memory is filled with `0xDE` before it is freed.
+	pub fn release(&mut self) { mem::drop(self.replace_vec(Vec::new())); }
+
+	/// Replaces the reserved memory with the capacity of `vec` and returns the previous memory
+	/// as an empty vec.
+	///
+	/// Any values in `vec` are dropped. In debug builds, the previous memory is filled with `0xDE`
+	/// before it is returned.
+	pub fn replace_vec(&mut self, mut vec: Vec<B>) -> Vec<B> {
+		vec.clear();
+
+		let mut old = mem::replace(&mut self.vec, vec);
+		poison(&mut old);
+		self.known_zero_bytes = 0;
+
+		old
+	}
+
+	/// Borrows the reusable memory as `capacity` zeroed `T`s, the returned borrow is full.
+	///
+	/// Only the bytes that are not already known to be zero are zeroed. They are known to be zero
+	/// after a previous zeroed borrow, until the memory is borrowed any other way or reallocated.
+	/// This avoids zeroing the same memory over and over when a zeroed buffer is reused in a loop.
+	///
+	/// This is meant for large, sparsely written buffers, such as histograms or visited flags,
+	/// where the caller already knows which values it changed and resetting just those
+	/// is much cheaper than zeroing the whole buffer again. Otherwise, use `borrow_mut_as`
+	/// and `push_zeroed`.
+	///
+	/// In debug builds, panics if the memory of the previous zeroed borrow was not zeroed again.
+	///
+	/// ### Safety
+	///
+	/// * All zero bytes must be a valid `T`.
+	/// * Before the borrow is dropped, every byte of the memory changed through it must be
+	///   zeroed again, otherwise the next zeroed borrow contains the changed values.
+	pub unsafe fn borrow_mut_as_zeroed<'mem, T: Copy>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> ReusableMemoryBorrow<'mem, T> {
+		let previous_ptr = self.vec.as_ptr();
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		self.vec.reserve(needed_length);
+
+		let known_zero_bytes =
+			if self.vec.as_ptr() == previous_ptr { self.known_zero_bytes } else { 0 };
+		debug_assert!(
+			std::slice::from_raw_parts(self.vec.as_ptr() as *const u8, known_zero_bytes)
+				.iter()
+				.all(|&byte| byte == 0),
+			"The previous zeroed borrow was not zeroed again"
+		);
+
+		let memory_ptr = self.aligned_ptr(mem::align_of::<T>());
+		let base_ptr = self.vec.as_mut_ptr() as *mut u8;
+		// Zero from the known zero bytes to the end of the borrow, including the alignment padding.
+		let end = (memory_ptr as usize - base_ptr as usize) + mem::size_of::<T>() * capacity.get();
+		if end > known_zero_bytes {
+			ptr::write_bytes(base_ptr.add(known_zero_bytes), 0, end - known_zero_bytes);
+		}
+		self.known_zero_bytes = known_zero_bytes.max(end);
+
+		let mut borrow = ReusableMemoryBorrow::from_raw_parts(
+			ptr::NonNull::new_unchecked(memory_ptr as *mut T),
+			capacity
+		);
+		borrow.set_len(capacity.get());
+
+		borrow
 	}
 
 	/// Borrows the reusable memory as a different type.
@@ -193,27 +466,347 @@ impl<B> ReusableMemory<B> {
 	/// This borrow is properly aligned and has at least the requested capacity.
 	///
 	/// Returns an error if `size_of::<T>() == 0`.
-	/// Also returns an error when the pointer could not be aligned properly for `T`.
 	pub fn borrow_mut_as<'mem, T>(
 		&'mem mut self, capacity: NonZeroUsize
 	) -> ReusableMemoryBorrow<'mem, T> {
 		let needed_length = self.needed_capacity_for::<T>(capacity);
 
+		// Give back the memory after a spike.
+		if (needed_length as f32) < self.shrink_threshold * self.vec.capacity() as f32 {
+			self.vec.shrink_to(needed_length);
+		}
+
 		// Reserve so at least `capacity` of `T`s fit, plus possible align offset.
 		self.vec.reserve(needed_length);
-		let memory_ptr = self.vec.as_mut_ptr();
 
-		// Compute the offset we need from the vec pointer to have the proper alignment.
-		let align_offset = memory_ptr.align_offset(mem::align_of::<T>());
-		if align_offset == std::usize::MAX {
-			panic!("Could not align pointer");
+		unsafe {
+			ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
+				capacity
+			)
+		}
+	}
+
+	/// Same as [`borrow_mut_as`](#method.borrow_mut_as), but uses `needed_length` instead of
+	/// computing it again with [`needed_capacity_for`](#method.needed_capacity_for).
+	///
+	/// ### Safety
+	///
+	/// * `needed_length` must be at least `self.needed_capacity_for::<T>(capacity)`.
+	pub unsafe fn borrow_mut_as_prereserved<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize, needed_length: usize
+	) -> ReusableMemoryBorrow<'mem, T> {
+		debug_assert!(needed_length >= self.needed_capacity_for::<T>(capacity));
+
+		self.vec.reserve(needed_length);
+
+		ReusableMemoryBorrow::from_raw_parts(
+			ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
+			capacity
+		)
+	}
+
+	/// Borrows the reusable memory the same as [`borrow_mut_as`](#method.borrow_mut_as),
+	/// but records the number of used bytes when the borrow is dropped.
+	///
+	/// In debug builds, the memory is also flagged as borrowed until the tracked borrow is dropped.
+	/// Borrowing the memory again while the flag is set panics. The borrow checker already prevents
+	/// this in safe code, but it catches aliasing borrows created through unsafe code, or a tracked
+	/// borrow that was leaked. A leaked tracked borrow keeps the flag set until
+	/// [`reset_tracking`](#method.reset_tracking) is called.
+	///
+	/// The maximum recorded value can be read using [`high_water_bytes`](#method.high_water_bytes).
+	pub fn borrow_mut_as_tracked<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> TrackedBorrow<'mem, T> {
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+
+		// Reserve so at least `capacity` of `T`s fit, plus possible align offset.
+		self.vec.reserve(needed_length);
+
+		let borrow = unsafe {
+			ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
+				capacity
+			)
+		};
+
+		TrackedBorrow::new(borrow, &mut self.tracking)
+	}
+
+	/// Returns the highest number of bytes used by a borrow from
+	/// [`borrow_mut_as_tracked`](#method.borrow_mut_as_tracked) at the time it was dropped.
+	///
+	/// Borrows from other methods are not tracked.
+	pub const fn high_water_bytes(&self) -> usize { self.tracking.high_water_bytes }
+
+	/// Clears the borrowed flag left behind by a tracked borrow that was leaked,
+	/// for example with `mem::forget`, so the memory can be borrowed again in debug builds.
+	///
+	/// The high-water mark is kept.
+	pub fn reset_tracking(&mut self) { self.tracking.borrowed = false; }
+
+	/// Borrows the reusable memory with capacity of `iter.len()` and pushes all values from `iter`.
+	///
+	/// If `iter` is empty, the returned borrow is empty with capacity of 1.
+	pub fn collect_exact<'mem, T, I: ExactSizeIterator<Item = T>>(
+		&'mem mut self, iter: I
+	) -> ReusableMemoryBorrow<'mem, T> {
+		let capacity = NonZeroUsize::new(iter.len().max(1)).unwrap();
+
+		let mut borrow = self.borrow_mut_as(capacity);
+		if borrow.push_from_exact_iter(iter).is_err() {
+			unreachable!("The capacity is at least `iter.len()`");
+		}
+
+		borrow
+	}
+
+	/// Borrows the reusable memory as a different type without reserving more memory.
+	///
+	/// Returns `Err(ReusableMemoryBorrowError::NotEnoughReservedMemory)` if the already reserved memory
+	/// is not enough for `capacity` of `T`s. This can be used to guarantee that no allocation happens.
+	pub fn try_borrow_mut_as<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> Result<ReusableMemoryBorrow<'mem, T>, ReusableMemoryBorrowError> {
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		if needed_length > self.vec.capacity() {
+			return Err(ReusableMemoryBorrowError::NotEnoughReservedMemory(needed_length))
+		}
+
+		unsafe {
+			Ok(ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
+				capacity
+			))
+		}
+	}
+
+	/// Same as [`borrow_mut_as`](#method.borrow_mut_as), but does not call `reserve` at all
+	/// if the borrow already fits into the reserved memory.
+	///
+	/// Unlike `borrow_mut_as`, this takes the actual alignment of the reserved memory into account
+	/// instead of the worst case, so a borrow after a larger borrow of a different type never reallocates.
+	pub fn borrow_mut_as_reusing<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> ReusableMemoryBorrow<'mem, T> {
+		assert_ne!(mem::size_of::<T>(), 0);
+
+		let offset = align_offset(self.vec.as_ptr() as *const u8, mem::align_of::<T>());
+		let reserved_bytes = self.vec.capacity() * mem::size_of::<B>();
+		let fits = mem::size_of::<T>()
+			.checked_mul(capacity.get())
+			.and_then(|bytes| offset.checked_add(bytes))
+			.is_some_and(|needed_bytes| needed_bytes <= reserved_bytes);
+		if !fits {
+			let needed_length = self.needed_capacity_for::<T>(capacity);
+			self.vec.reserve(needed_length);
 		}
 
 		unsafe {
 			ReusableMemoryBorrow::from_raw_parts(
-				ptr::NonNull::new_unchecked(memory_ptr.add(align_offset) as *mut T),
+				ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
 				capacity
 			)
 		}
 	}
+
+	/// Same as [`borrow_mut_as`](#method.borrow_mut_as), but also returns whether the reserved memory
+	/// had to grow to fit the borrow.
+	pub fn borrow_mut_as_reporting<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> (ReusableMemoryBorrow<'mem, T>, bool) {
+		let previous_capacity = self.vec.capacity();
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		self.vec.reserve(needed_length);
+		let grew = self.vec.capacity() != previous_capacity;
+
+		let borrow = unsafe {
+			ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(self.aligned_ptr(mem::align_of::<T>()) as *mut T),
+				capacity
+			)
+		};
+
+		(borrow, grew)
+	}
+
+	/// Same as [`borrow_mut_as`](#method.borrow_mut_as), but also returns how many more `T`s
+	/// would have fit into the reserved memory.
+	///
+	/// Borrowing with `capacity` increased by that number does not reallocate, unless
+	/// [`borrow_mut_as`](#method.borrow_mut_as) shrinks the reserved memory because of a threshold
+	/// set by [`set_shrink_threshold`](#method.set_shrink_threshold).
+	pub fn borrow_mut_as_with_spare<'mem, T>(
+		&'mem mut self, capacity: NonZeroUsize
+	) -> (ReusableMemoryBorrow<'mem, T>, usize) {
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		self.vec.reserve(needed_length);
+
+		let memory_ptr = self.aligned_ptr(mem::align_of::<T>());
+		let usable_bytes = self.vec.capacity() * mem::size_of::<B>()
+			- (memory_ptr as usize - self.vec.as_ptr() as usize);
+		let spare = usable_bytes / mem::size_of::<T>() - capacity.get();
+
+		let borrow = unsafe {
+			ReusableMemoryBorrow::from_raw_parts(ptr::NonNull::new_unchecked(memory_ptr as *mut T), capacity)
+		};
+
+		(borrow, spare)
+	}
+
+	/// Borrows the reusable memory as a different type with a capacity of `N`.
+	///
+	/// `N` must not be zero, which is checked at compile time:
+	///
+	/// ```compile_fail
+	/// let mut memory: reusable_memory::ReusableMemory = reusable_memory::ReusableMemory::new();
+	/// let borrow = memory.borrow_mut_as_n::<u32, 0>();
+	/// ```
+	pub fn borrow_mut_as_n<'mem, T, const N: usize>(&'mem mut self) -> ReusableMemoryBorrow<'mem, T> {
+		self.borrow_mut_as(ConstCapacity::<N>::CAPACITY)
+	}
+
+	/// Borrows the reusable memory as a different type with a `usize` capacity.
+	///
+	/// Returns `None` if `capacity == 0`, otherwise works as [`borrow_mut_as`](#method.borrow_mut_as).
+	pub fn try_borrow_mut_as_n<'mem, T>(
+		&'mem mut self, capacity: usize
+	) -> Option<ReusableMemoryBorrow<'mem, T>> {
+		NonZeroUsize::new(capacity).map(move |capacity| self.borrow_mut_as(capacity))
+	}
+
+	/// Borrows the reusable memory as a different type, keeping the rest of the reserved memory
+	/// available to be borrowed later as another type.
+	///
+	/// See [`SplitScratch`](borrow/struct.SplitScratch.html).
+	pub fn split_scratch<'mem, T>(&'mem mut self, capacity: NonZeroUsize) -> SplitScratch<'mem, T> {
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		self.vec.reserve(needed_length);
+
+		let memory_ptr = self.aligned_ptr(mem::align_of::<T>());
+		let end = unsafe {
+			(self.vec.as_mut_ptr() as *mut u8).add(self.vec.capacity() * mem::size_of::<B>())
+		};
+
+		unsafe {
+			SplitScratch::new(
+				ReusableMemoryBorrow::from_raw_parts(
+					ptr::NonNull::new_unchecked(memory_ptr as *mut T),
+					capacity
+				),
+				end
+			)
+		}
+	}
+
+	/// Borrows the reusable memory as a raw slice of uninitialized `T`s.
+	///
+	/// Unlike [`borrow_mut_as`](#method.borrow_mut_as), there is no length tracking and no `Drop`,
+	/// the caller is responsible for keeping track of which elements are initialized.
+	/// Values written through the slice are never dropped, which is meant for `Copy` types.
+	pub fn borrow_raw_mut_as<T>(&mut self, capacity: NonZeroUsize) -> &mut [MaybeUninit<T>] {
+		let needed_length = self.needed_capacity_for::<T>(capacity);
+		self.vec.reserve(needed_length);
+
+		unsafe {
+			std::slice::from_raw_parts_mut(
+				self.aligned_ptr(mem::align_of::<T>()) as *mut MaybeUninit<T>,
+				capacity.get()
+			)
+		}
+	}
+
+	/// Reserves memory for `layout` and returns a pointer to it aligned to `layout.align()`.
+	///
+	/// This is the untyped building block below [`borrow_mut_as`](#method.borrow_mut_as),
+	/// the caller is responsible for typing the memory and tracking what is initialized.
+	///
+	/// ### Safety
+	///
+	/// * The returned memory is uninitialized.
+	/// * The returned pointer is only valid for reads and writes of `layout.size()` bytes until `self`
+	///   is borrowed, reserved or dropped again, as these may reuse or reallocate the memory.
+	pub unsafe fn borrow_mut_raw(&mut self, layout: Layout) -> ptr::NonNull<u8> {
+		// Add `align - 1` to `needed_bytes` if the alignment is more than align of `B`.
+		let align_bump = if mem::align_of::<B>() >= layout.align() { 0 } else { layout.align() - 1 };
+		let needed_length = (layout.size() + align_bump).div_ceil(mem::size_of::<B>());
+		self.vec.reserve(needed_length);
+
+		ptr::NonNull::new_unchecked(self.aligned_ptr(layout.align()))
+	}
+
+	/// Returns pointer into the reserved memory aligned to `align`.
+	///
+	/// In debug builds, panics if a tracked borrow is still alive.
+	///
+	/// Resets the known zero bytes, because the returned pointer may be written through.
+	fn aligned_ptr(&mut self, align: usize) -> *mut u8 {
+		debug_assert!(!self.tracking.borrowed, "Reusable memory is already borrowed");
+		// Any borrow may write into the memory.
+		self.known_zero_bytes = 0;
+
+		let memory_ptr = self.vec.as_mut_ptr() as *mut u8;
+
+		// Compute the offset we need from the vec pointer to have the proper alignment.
+		unsafe { memory_ptr.add(align_offset(memory_ptr, align)) }
+	}
+
+	/// Returns the number of `B`s the reserved memory can hold.
+	pub fn capacity(&self) -> usize { self.vec.capacity() }
+
+	/// Returns a pointer to the reserved memory.
+	///
+	/// This is intended for diagnostics only, for example to observe whether a borrow reallocated.
+	pub fn backing_ptr(&self) -> *const B { self.vec.as_ptr() }
+
+	/// Returns statistics of the reserved memory.
+	///
+	/// Borrows are not tracked, so this only reports the reserved memory, not the used memory.
+	/// See [`high_water_bytes`](#method.high_water_bytes) for that.
+	pub fn stats(&self) -> MemoryStats {
+		MemoryStats {
+			reserved_bytes: self.vec.capacity() * mem::size_of::<B>(),
+			element_size: mem::size_of::<B>(),
+			capacity_elements: self.vec.capacity()
+		}
+	}
+}
+/// A clone has new memory, so none of it is known to be zero.
+impl<B: Clone> Clone for ReusableMemory<B> {
+	fn clone(&self) -> Self {
+		ReusableMemory {
+			vec: self.vec.clone(),
+			tracking: self.tracking.clone(),
+			shrink_threshold: self.shrink_threshold,
+			..Self::from_empty_vec(Vec::new())
+		}
+	}
+}
+impl<B> Default for ReusableMemory<B> {
+	fn default() -> Self { Self::new() }
+}
+/// Compares only the capacity of the reserved memory.
+///
+/// The contents of the memory are meaningless outside of borrows, so they are never compared.
+impl<B> PartialEq for ReusableMemory<B> {
+	fn eq(&self, other: &Self) -> bool { self.capacity() == other.capacity() }
+}
+impl<B> Eq for ReusableMemory<B> {}
+/// Hashes only the capacity of the reserved memory, consistent with `PartialEq`.
+impl<B> Hash for ReusableMemory<B> {
+	fn hash<H: Hasher>(&self, state: &mut H) { self.capacity().hash(state); }
+}
+/// Reuses the reserved memory of the vec, dropping any values in it.
+impl<B> TryFrom<Vec<B>> for ReusableMemory<B> {
+	type Error = ZeroSizedBaseError;
+
+	fn try_from(mut vec: Vec<B>) -> Result<Self, Self::Error> {
+		if mem::size_of::<B>() == 0 {
+			return Err(ZeroSizedBaseError)
+		}
+
+		vec.clear();
+		Ok(Self::from_empty_vec(vec))
+	}
 }
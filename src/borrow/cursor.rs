@@ -0,0 +1,101 @@
+use std::{io, ptr};
+
+use super::{ReusableMemoryBorrow, ReusableMemoryBorrowError};
+
+/// Byte cursor over a borrow of `u8`s.
+///
+/// Writing through `std::io::Write` overwrites the stored bytes at the current position
+/// and pushes new bytes past the end, up to the capacity of the borrow.
+///
+/// Reading through `std::io::Read` copies the stored bytes from the current position.
+pub struct BorrowCursor<'bor, 'mem> {
+	borrow: &'bor mut ReusableMemoryBorrow<'mem, u8>,
+	position: usize
+}
+impl<'bor, 'mem: 'bor> BorrowCursor<'bor, 'mem> {
+	pub(super) fn new(borrow: &'bor mut ReusableMemoryBorrow<'mem, u8>) -> Self {
+		BorrowCursor { borrow, position: 0 }
+	}
+
+	/// Returns the current position of the cursor.
+	pub const fn position(&self) -> usize { self.position }
+
+	/// Moves the cursor to `position`.
+	///
+	/// Panics if `position` is greater than the length of the borrow.
+	pub fn seek(&mut self, position: usize) {
+		assert!(position <= self.borrow.len());
+
+		self.position = position;
+	}
+
+	/// Overwrites the stored bytes starting at `position` without moving the cursor.
+	///
+	/// This is useful for backfilling a length prefix after writing the payload.
+	///
+	/// Panics if the written range is not within the length of the borrow.
+	pub fn write_at(&mut self, position: usize, bytes: &[u8]) {
+		self.borrow[position .. position + bytes.len()].copy_from_slice(bytes);
+	}
+}
+impl io::Write for BorrowCursor<'_, '_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let len = self.borrow.len();
+
+		// Overwrite the stored bytes first
+		let overwritten = buf.len().min(len - self.position);
+		self.borrow[self.position .. self.position + overwritten]
+			.copy_from_slice(&buf[.. overwritten]);
+
+		// Then push the rest while there is capacity
+		let pushed = (buf.len() - overwritten).min(self.borrow.capacity().get() - len);
+		unsafe {
+			std::ptr::copy_nonoverlapping(
+				buf.as_ptr().add(overwritten),
+				self.borrow.as_mut_ptr().add(len),
+				pushed
+			);
+			self.borrow.set_len(len + pushed);
+		}
+
+		self.position += overwritten + pushed;
+		Ok(overwritten + pushed)
+	}
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl io::Read for BorrowCursor<'_, '_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let unread = &self.borrow[self.position ..];
+		let count = buf.len().min(unread.len());
+		buf[.. count].copy_from_slice(&unread[.. count]);
+
+		self.position += count;
+		Ok(count)
+	}
+}
+
+impl<'mem> ReusableMemoryBorrow<'mem, u8> {
+	/// Creates a byte cursor starting at the beginning of the borrow.
+	pub fn cursor(&mut self) -> BorrowCursor<'_, 'mem> { BorrowCursor::new(self) }
+
+	/// Returns the stored bytes as an `IoSlice` for vectored writes.
+	pub fn as_io_slice(&self) -> io::IoSlice<'_> { io::IoSlice::new(self.as_slice()) }
+
+	/// Appends `n` zero bytes.
+	///
+	/// Returns an error and leaves the borrow unchanged if there is not enough capacity.
+	pub fn push_zeroed(&mut self, n: usize) -> Result<(), ReusableMemoryBorrowError> {
+		if n > self.remaining_capacity() {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity()))
+		}
+
+		unsafe {
+			ptr::write_bytes(self.as_mut_ptr().add(self.len()), 0, n);
+			self.set_len(self.len() + n);
+		}
+
+		Ok(())
+	}
+}
@@ -1,12 +1,34 @@
 use std::{
 	fmt,
-	ops::{Bound, Range, RangeBounds}
+	mem,
+	ops::{Bound, Range, RangeBounds},
+	ptr
 };
 
 use super::ReusableMemoryBorrow;
 
 // Most of this code is copied from std Vec
 
+/// Converts `range` into a `Range` bounded by `len`.
+///
+/// Panics if the start is greater than the end or if the end is greater than `len`.
+pub(super) fn bounded_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+	let start = match range.start_bound() {
+		Bound::Included(&n) => n,
+		Bound::Excluded(&n) => n + 1,
+		Bound::Unbounded => 0
+	};
+	let end = match range.end_bound() {
+		Bound::Included(&n) => n + 1,
+		Bound::Excluded(&n) => n,
+		Bound::Unbounded => len
+	};
+	assert!(start <= end);
+	assert!(end <= len);
+
+	start .. end
+}
+
 pub struct BorrowDrainIter<'bor, 'mem, T: 'mem> {
 	borrow: &'bor mut ReusableMemoryBorrow<'mem, T>,
 	drain_range: Range<usize>,
@@ -21,18 +43,7 @@ impl<'bor, 'mem: 'bor, T: 'mem> BorrowDrainIter<'bor, 'mem, T> {
 		borrow: &'bor mut ReusableMemoryBorrow<'mem, T>, range: impl RangeBounds<usize>
 	) -> Self {
 		let len = borrow.len();
-		let start = match range.start_bound() {
-			Bound::Included(&n) => n,
-			Bound::Excluded(&n) => n + 1,
-			Bound::Unbounded => 0
-		};
-		let end = match range.end_bound() {
-			Bound::Included(&n) => n + 1,
-			Bound::Excluded(&n) => n,
-			Bound::Unbounded => len
-		};
-		assert!(start <= end);
-		assert!(end <= len);
+		let Range { start, end } = bounded_range(range, len);
 
 		unsafe {
 			// Safety in case Drain is leaked
@@ -41,17 +52,33 @@ impl<'bor, 'mem: 'bor, T: 'mem> BorrowDrainIter<'bor, 'mem, T> {
 			Self { borrow, drain_range: start .. end, tail_start: end, tail_len: len - end }
 		}
 	}
+
+	/// Returns a view of the items that have not been yielded yet.
+	pub fn as_slice(&self) -> &[T] {
+		unsafe {
+			std::slice::from_raw_parts(
+				self.borrow.as_ptr().add(self.drain_range.start),
+				self.drain_range.len()
+			)
+		}
+	}
+
+	/// Returns a mutable view of the items that have not been yielded yet.
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		unsafe {
+			std::slice::from_raw_parts_mut(
+				self.borrow.as_mut_ptr().add(self.drain_range.start),
+				self.drain_range.len()
+			)
+		}
+	}
+
+	/// Same as [`as_mut_slice`](#method.as_mut_slice).
+	pub fn remaining_mut(&mut self) -> &mut [T] { self.as_mut_slice() }
 }
 impl<T: fmt::Debug> fmt::Debug for BorrowDrainIter<'_, '_, T> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		f.debug_tuple("BorrowDrainIter")
-			.field(unsafe {
-				&std::slice::from_raw_parts(
-					self.borrow.as_ptr().add(self.drain_range.start),
-					self.drain_range.end - self.drain_range.start
-				)
-			})
-			.finish()
+		f.debug_tuple("BorrowDrainIter").field(&self.as_slice()).finish()
 	}
 }
 impl<T> Iterator for BorrowDrainIter<'_, '_, T> {
@@ -60,23 +87,44 @@ impl<T> Iterator for BorrowDrainIter<'_, '_, T> {
 	fn next(&mut self) -> Option<T> {
 		self.drain_range
 			.next()
-			.map(|offset| unsafe { std::ptr::read(self.borrow.as_ptr().add(offset)) })
+			.map(|offset| unsafe { std::ptr::read(self.borrow.as_mut_ptr().add(offset)) })
 	}
 
 	fn size_hint(&self) -> (usize, Option<usize>) { self.drain_range.size_hint() }
+
+	fn nth(&mut self, n: usize) -> Option<T> {
+		let skipped = n.min(self.drain_range.len());
+		let start = self.drain_range.start;
+		// Advance first so that a panicking drop can't cause a double drop.
+		self.drain_range.start += skipped;
+
+		unsafe {
+			ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+				self.borrow.as_mut_ptr().add(start),
+				skipped
+			));
+		}
+
+		self.next()
+	}
 }
 impl<T> DoubleEndedIterator for BorrowDrainIter<'_, '_, T> {
 	fn next_back(&mut self) -> Option<T> {
 		self.drain_range
 			.next_back()
-			.map(|offset| unsafe { std::ptr::read(self.borrow.as_ptr().add(offset)) })
+			.map(|offset| unsafe { std::ptr::read(self.borrow.as_mut_ptr().add(offset)) })
 	}
 }
 impl<T> ExactSizeIterator for BorrowDrainIter<'_, '_, T> {}
 impl<T> Drop for BorrowDrainIter<'_, '_, T> {
 	fn drop(&mut self) {
-		// exhaust self first
-		self.for_each(drop);
+		// exhaust self first, there is nothing to drop for types without drop glue
+		// the values are dropped back-to-front, same as in `ReusableMemoryBorrow::truncate`
+		if mem::needs_drop::<T>() {
+			self.rev().for_each(drop);
+		} else {
+			self.drain_range.start = self.drain_range.end;
+		}
 
 		if self.tail_len > 0 {
 			unsafe {
@@ -84,9 +132,8 @@ impl<T> Drop for BorrowDrainIter<'_, '_, T> {
 				let tail = self.tail_start;
 				// There is some tail left and we need to memmove it
 				if start != tail {
-					let src = self.borrow.as_ptr().add(tail);
-					let dst = self.borrow.as_mut_ptr().add(start);
-					std::ptr::copy(src, dst, self.tail_len);
+					let ptr = self.borrow.as_mut_ptr();
+					std::ptr::copy(ptr.add(tail), ptr.add(start), self.tail_len);
 				}
 
 				self.borrow.set_len(start + self.tail_len);
@@ -1,6 +1,6 @@
 //! This module contains sort of manual "specializations" for pushing from iterators.
 
-use super::ReusableMemoryBorrow;
+use super::{ReusableMemoryBorrow, ReusableMemoryBorrowError};
 
 impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 	/// Pushes new values from `iter: impl Iterator` while possible.
@@ -43,6 +43,31 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 		Err(iter)
 	}
 
+	/// Pushes new values from `iter: impl IntoIterator` while possible.
+	///
+	/// Same as [`push_from_iter`](#method.push_from_iter), but takes `IntoIterator`.
+	pub fn extend_bounded<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), I::IntoIter> {
+		self.push_from_iter(iter.into_iter())
+	}
+
+	/// Pushes new values from `iter: impl IntoIterator` while possible and drops the rest.
+	///
+	/// Unlike [`push_from_iter`](#method.push_from_iter), this exhausts `iter`.
+	/// Values that do not fit are dropped as they are pulled from the iterator.
+	///
+	/// Returns the number of values stored.
+	pub fn extend_truncating<I: IntoIterator<Item = T>>(&mut self, iter: I) -> usize {
+		let mut stored = 0;
+		for value in iter {
+			// `push` drops the value if there is not enough capacity.
+			if self.push(value).is_ok() {
+				stored += 1;
+			}
+		}
+
+		stored
+	}
+
 	pub fn push_from_iter_size_hint<I: Iterator<Item = T>>(&mut self, iter: I) -> Result<(), I> {
 		let hint = iter.size_hint();
 		let hinted_max = hint.1.unwrap_or(hint.0);
@@ -60,8 +85,10 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 	pub fn push_from_exact_iter<I: ExactSizeIterator<Item = T>>(
 		&mut self, iter: I
 	) -> Result<(), I> {
-		if self.len() + iter.len() > self.capacity.get() {
-			return Err(iter)
+		// `iter.len()` is not trusted, so check for overflow instead of wrapping around.
+		match self.len().checked_add(iter.len()) {
+			Some(needed) if needed <= self.capacity.get() => (),
+			_ => return Err(iter)
 		}
 
 		for elem in iter {
@@ -70,4 +97,16 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 
 		Ok(())
 	}
+
+	/// Pushes all values from `iter: impl IntoIterator` with an exact size.
+	///
+	/// Returns an error and leaves `self` unchanged if there is not enough capacity for all of them.
+	pub fn push_all<I, J>(&mut self, iter: I) -> Result<(), ReusableMemoryBorrowError>
+	where
+		I: IntoIterator<Item = T, IntoIter = J>,
+		J: ExactSizeIterator<Item = T>
+	{
+		self.push_from_exact_iter(iter.into_iter())
+			.map_err(|_| ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity))
+	}
 }
@@ -1,20 +1,36 @@
 use std::{
 	borrow::{Borrow, BorrowMut},
+	collections::HashSet,
+	hash::Hash,
 	marker::PhantomData,
-	mem,
+	mem::{self, MaybeUninit},
 	num::NonZeroUsize,
-	ops::{Deref, DerefMut, RangeBounds},
+	ops::{Deref, DerefMut, Range, RangeBounds},
 	ptr
 };
 
+pub mod cursor;
 pub mod drain;
 mod manual_specialization;
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod ops;
+mod split;
+mod tracked;
 
+pub use cursor::BorrowCursor;
 pub use drain::BorrowDrainIter;
+pub use split::SplitScratch;
+pub(crate) use tracked::BorrowTracking;
+pub use tracked::TrackedBorrow;
 
 #[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum ReusableMemoryBorrowError {
-	NotEnoughCapacity(NonZeroUsize)
+	NotEnoughCapacity(NonZeroUsize),
+	/// The reserved memory is not enough, the contained value is the needed length in `B`s.
+	NotEnoughReservedMemory(usize),
+	/// One of the borrowed types is zero sized.
+	ZeroSizedType
 }
 impl std::fmt::Display for ReusableMemoryBorrowError {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -22,6 +38,10 @@ impl std::fmt::Display for ReusableMemoryBorrowError {
 			ReusableMemoryBorrowError::NotEnoughCapacity(capacity) => {
 				write!(f, "Not enough capacity ({}) to push another element.", capacity)
 			}
+			ReusableMemoryBorrowError::NotEnoughReservedMemory(needed) => {
+				write!(f, "Not enough reserved memory ({} needed) to borrow without reallocating.", needed)
+			}
+			ReusableMemoryBorrowError::ZeroSizedType => write!(f, "Cannot borrow zero sized types.")
 		}
 	}
 }
@@ -29,6 +49,21 @@ impl std::error::Error for ReusableMemoryBorrowError {
 	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
 }
 
+/// Layout of a borrow as returned by `ReusableMemoryBorrow::dump_layout`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BorrowLayout {
+	/// Address of the first element.
+	pub address: usize,
+	/// Number of elements stored.
+	pub len: usize,
+	/// Number of elements that can be stored.
+	pub capacity: usize,
+	/// Alignment of the element type.
+	pub align: usize,
+	/// Size of the whole borrowed memory in bytes.
+	pub byte_span: usize
+}
+
 /// Borrow of the reusable memory.
 ///
 /// This struct borrows a properly aligned subset of the memory owned by `ReusableMemory`.
@@ -56,12 +91,35 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 	/// Returns number of `T`s currently stored.
 	pub const fn len(&self) -> usize { self.len }
 
-	/// Returns number of `T`s currently stored.
-	pub unsafe fn set_len(&mut self, len: usize) { self.len = len; }
+	/// Returns `true` if no `T`s are currently stored.
+	pub const fn is_empty(&self) -> bool { self.len == 0 }
+
+	/// Sets the number of `T`s currently stored.
+	///
+	/// When growing the length, initialize the new values first, for example through
+	/// [`spare_capacity_mut`](#method.spare_capacity_mut), and only then call this.
+	/// When shrinking, the values past the new length are not dropped.
+	///
+	/// ### Safety
+	///
+	/// * `len` must not be greater than `capacity`.
+	/// * The first `len` elements must be initialized, otherwise `as_slice` and
+	///   every other method reading the values reads uninitialized memory.
+	pub unsafe fn set_len(&mut self, len: usize) {
+		debug_assert!(len <= self.capacity.get());
+
+		self.len = len;
+	}
 
 	/// Returns number of `T`s that can be stored.
 	pub const fn capacity(&self) -> NonZeroUsize { self.capacity }
 
+	/// Returns number of `T`s that can be stored as `usize`, same as `self.capacity().get()`.
+	pub const fn capacity_usize(&self) -> usize { self.capacity.get() }
+
+	/// Returns number of `T`s that can still be pushed.
+	pub const fn remaining_capacity(&self) -> usize { self.capacity.get() - self.len }
+
 	/// Returns a const pointer to the data.
 	pub const fn as_ptr(&self) -> *const T { self.memory.as_ptr() as *const _ }
 
@@ -69,38 +127,90 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 	pub const fn as_mut_ptr(&self) -> *mut T { self.memory.as_ptr() }
 
 	/// Returns a slice view of the data.
+	///
+	/// This relies on the first `len()` values being initialized, which only `set_len` can break.
 	pub fn as_slice(&self) -> &[T] {
+		debug_assert!(self.len <= self.capacity.get());
+
 		unsafe { std::slice::from_raw_parts(self.as_ptr(), self.len()) }
 	}
 
 	/// Returns a mut slice view of the data.
 	pub fn as_mut_slice(&mut self) -> &mut [T] {
-		unsafe { std::slice::from_raw_parts_mut(self.as_ptr() as *mut _, self.len()) }
+		debug_assert!(self.len <= self.capacity.get());
+
+		unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr(), self.len()) }
 	}
 
 	/// Drops all pushed values and sets the length to 0.
-	pub fn clear(&mut self) {
+	///
+	/// The values are dropped back-to-front, the same as in [`truncate`](#method.truncate).
+	pub fn clear(&mut self) { self.truncate(0); }
+
+	/// Moves all values out back-to-front and passes them to `f`, setting the length to 0.
+	///
+	/// If `f` panics, the values that were not passed to it yet stay in the borrow.
+	pub fn clear_with<F: FnMut(T)>(&mut self, mut f: F) {
+		while let Some(value) = self.pop() {
+			f(value);
+		}
+	}
+
+	/// Drops the values after the first `len` values, keeping the capacity.
+	///
+	/// The values are dropped back-to-front. This order is shared by `clear`,
+	/// dropping the borrow and dropping the values left in a drain iterator.
+	///
+	/// Does nothing if `len >= self.len()`.
+	pub fn truncate(&mut self, len: usize) {
+		if len >= self.len {
+			return
+		}
+
 		if mem::needs_drop::<T>() {
 			unsafe {
 				let mut ptr = self.memory.as_ptr().add(self.len);
 				let current_len = self.len;
 				// Panic safety, rather leak than double-drop.
 				// Vec uses internal `SetLenOnDrop` but this is okay too.
-				self.len = 0;
+				self.len = len;
 
-				for _ in 0 .. current_len {
+				for _ in len .. current_len {
 					ptr = ptr.offset(-1);
 					ptr::drop_in_place(ptr);
 				}
 			}
 		} else {
-			self.len = 0;
+			self.len = len;
+		}
+	}
+
+	/// Same as [`truncate`](#method.truncate), but returns the number of dropped values.
+	///
+	/// Returns `0` if `new_len >= self.len()`.
+	pub fn truncate_counted(&mut self, new_len: usize) -> usize {
+		let dropped = self.len.saturating_sub(new_len);
+		self.truncate(new_len);
+
+		dropped
+	}
+
+	/// Keeps only the first `n` values, same as [`truncate`](#method.truncate).
+	pub fn keep_first(&mut self, n: usize) { self.truncate(n); }
+
+	/// Keeps only the last `n` values, dropping the values before them and moving them to the front.
+	///
+	/// Does nothing if `n >= self.len()`.
+	pub fn keep_last(&mut self, n: usize) {
+		if n < self.len {
+			let end = self.len - n;
+			drop(self.drain(.. end));
 		}
 	}
 
 	/// Pushes a new value.
 	///
-	/// Returns Err if there is not enough capacity.
+	/// Returns Err if there is not enough capacity, in which case the value is dropped.
 	pub fn push(&mut self, value: T) -> Result<(), ReusableMemoryBorrowError> {
 		if self.len == self.capacity.get() {
 			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity))
@@ -116,11 +226,52 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 		Ok(())
 	}
 
+	/// Same as [`push`](#method.push), but returns the remaining capacity after the push.
+	///
+	/// Returns Err if there is not enough capacity, in which case the value is dropped.
+	#[must_use = "the value is dropped if there is not enough capacity"]
+	pub fn push_reporting(&mut self, value: T) -> Result<usize, ReusableMemoryBorrowError> {
+		self.push(value)?;
+
+		Ok(self.remaining_capacity())
+	}
+
+	/// Pushes all values from an array.
+	///
+	/// Returns the array back with an error if there is not enough capacity for all of them.
+	pub fn push_array<const N: usize>(
+		&mut self, values: [T; N]
+	) -> Result<(), ([T; N], ReusableMemoryBorrowError)> {
+		if N > self.remaining_capacity() {
+			return Err((values, ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity)))
+		}
+
+		unsafe {
+			ptr::write(self.as_mut_ptr().add(self.len) as *mut [T; N], values);
+		}
+		self.len += N;
+
+		Ok(())
+	}
+
+	/// Pushes a new value if there is enough capacity.
+	///
+	/// Returns `false` if there is not enough capacity, in which case the value is dropped.
+	pub fn push_if_fits(&mut self, value: T) -> bool { self.push(value).is_ok() }
+
 	/// Pops from the end.
 	///
 	/// Returns `None` if `self.len() == 0`.
+	///
+	/// ```compile_fail
+	/// #![deny(unused_must_use)]
+	/// # let mut memory: reusable_memory::ReusableMemory = reusable_memory::ReusableMemory::new();
+	/// # let mut borrow = memory.borrow_mut_as::<u8>(std::num::NonZeroUsize::new(1).unwrap());
+	/// borrow.pop();
+	/// ```
+	#[must_use]
 	pub fn pop(&mut self) -> Option<T> {
-		if self.len() == 0 {
+		if self.is_empty() {
 			return None
 		}
 
@@ -132,14 +283,771 @@ impl<'mem, T> ReusableMemoryBorrow<'mem, T> {
 		Some(value)
 	}
 
+	/// Pops from the front.
+	///
+	/// Unlike [`pop`](#method.pop), this is O(n) because the remaining values are shifted to the front.
+	///
+	/// Returns `None` if `self.len() == 0`.
+	pub fn pop_front(&mut self) -> Option<T> {
+		if self.is_empty() {
+			return None
+		}
+
+		let value = unsafe {
+			let value = ptr::read(self.as_ptr());
+			self.len -= 1;
+			ptr::copy(self.as_ptr().add(1), self.as_mut_ptr(), self.len);
+
+			value
+		};
+
+		Some(value)
+	}
+
+	/// Removes the first value and replaces it with the last one.
+	///
+	/// Unlike [`pop_front`](#method.pop_front), this is O(1) but does not keep the order.
+	///
+	/// Returns `None` if `self.len() == 0`.
+	pub fn swap_remove_front(&mut self) -> Option<T> {
+		if self.is_empty() {
+			return None
+		}
+
+		let value = unsafe {
+			let value = ptr::read(self.as_ptr());
+			self.len -= 1;
+			ptr::copy(self.as_ptr().add(self.len), self.as_mut_ptr(), 1);
+
+			value
+		};
+
+		Some(value)
+	}
+
+	/// Inserts a value at `index`, shifting all values after it to the back.
+	///
+	/// Returns Err if there is not enough capacity, in which case the value is dropped.
+	///
+	/// Panics if `index > self.len()`.
+	#[must_use = "the value is dropped if there is not enough capacity"]
+	pub fn insert(&mut self, index: usize, value: T) -> Result<(), ReusableMemoryBorrowError> {
+		assert!(index <= self.len);
+
+		if self.len == self.capacity.get() {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity))
+		}
+
+		unsafe {
+			let ptr = self.as_mut_ptr().add(index);
+			ptr::copy(ptr, ptr.add(1), self.len - index);
+			ptr::write(ptr, value);
+
+			self.len += 1;
+		}
+
+		Ok(())
+	}
+
+	/// Removes and returns the value at `index`, shifting all values after it to the front.
+	///
+	/// Panics if `index >= self.len()`.
+	pub fn remove(&mut self, index: usize) -> T {
+		assert!(index < self.len);
+
+		unsafe {
+			let ptr = self.as_mut_ptr().add(index);
+			let value = ptr::read(ptr);
+			self.len -= 1;
+			ptr::copy(ptr.add(1), ptr, self.len - index);
+
+			value
+		}
+	}
+
+	/// Same as [`insert`](#method.insert), named after `IndexMap::shift_insert`.
+	#[must_use = "the value is dropped if there is not enough capacity"]
+	pub fn shift_insert(&mut self, index: usize, value: T) -> Result<(), ReusableMemoryBorrowError> {
+		self.insert(index, value)
+	}
+
+	/// Same as [`remove`](#method.remove), named after `IndexMap::shift_remove`.
+	pub fn shift_remove(&mut self, index: usize) -> T { self.remove(index) }
+
+	/// Returns the last element, the one `pop` would remove.
+	///
+	/// Returns `None` if `self.len() == 0`.
+	pub fn peek(&self) -> Option<&T> { self.as_slice().last() }
+
+	/// Returns the last element mutably, the one `pop` would remove.
+	///
+	/// Returns `None` if `self.len() == 0`.
+	pub fn peek_mut(&mut self) -> Option<&mut T> { self.as_mut_slice().last_mut() }
+
 	/// Creates a draining iterator that removes the specified range in the borrow and yields the removed items.
 	///
-	/// This functions exactly as `Vec::drain`.
+	/// This functions exactly as `Vec::drain`. The values that are not consumed
+	/// are dropped back-to-front when the iterator is dropped.
 	pub fn drain<'bor>(
 		&'bor mut self, range: impl RangeBounds<usize>
 	) -> BorrowDrainIter<'bor, 'mem, T> {
 		BorrowDrainIter::new(self, range)
 	}
+
+	/// Moves the specified range into `dst` in reverse order.
+	///
+	/// Returns an error and leaves both unchanged if `dst` does not have enough capacity.
+	pub fn drain_rev_into(
+		&mut self, range: impl RangeBounds<usize>, dst: &mut ReusableMemoryBorrow<'_, T>
+	) -> Result<(), ReusableMemoryBorrowError> {
+		let range = drain::bounded_range(range, self.len);
+		if range.len() > dst.remaining_capacity() {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(dst.capacity))
+		}
+
+		for value in self.drain(range).rev() {
+			dst.push(value).unwrap();
+		}
+
+		Ok(())
+	}
+
+	/// Creates a draining iterator that yields all items, same as `drain(..)`.
+	///
+	/// The borrow is empty afterwards and the borrowed memory stays reserved in `ReusableMemory`,
+	/// so it can be borrowed again as another type without reallocating. The exception is
+	/// `ReusableMemory::borrow_mut_as` with a shrink threshold set by `set_shrink_threshold`,
+	/// which shrinks the reserved memory if the new borrow needs much less of it.
+	pub fn drain_owned<'bor>(&'bor mut self) -> BorrowDrainIter<'bor, 'mem, T> { self.drain(..) }
+
+	/// Creates a draining iterator that yields the values after `new_len`, same as `drain(new_len ..)`.
+	///
+	/// Panics if `new_len` is greater than `self.len()`.
+	pub fn drain_tail<'bor>(&'bor mut self, new_len: usize) -> BorrowDrainIter<'bor, 'mem, T> {
+		self.drain(new_len ..)
+	}
+
+	/// Creates an iterator over references to the specified range in the borrow.
+	///
+	/// Takes the same ranges as [`drain`](#method.drain), but leaves the borrow unchanged.
+	pub fn drain_peek(&self, range: impl RangeBounds<usize>) -> std::slice::Iter<'_, T> {
+		self.as_slice()[drain::bounded_range(range, self.len())].iter()
+	}
+
+	/// Returns an iterator over `tile` sized mutable subslices of the data.
+	///
+	/// The last tile will be shorter if `self.len()` is not divisible by `tile`.
+	///
+	/// This functions exactly as `slice::chunks_mut`.
+	pub fn tiles_mut(&mut self, tile: NonZeroUsize) -> std::slice::ChunksMut<'_, T> {
+		self.as_mut_slice().chunks_mut(tile.get())
+	}
+
+	/// Returns an iterator over `tile` sized mutable subslices of the data.
+	///
+	/// If `self.len()` is not divisible by `tile`, the last values are not yielded,
+	/// they can be accessed through `into_remainder` on the returned iterator.
+	///
+	/// This functions exactly as `slice::chunks_exact_mut`.
+	pub fn tiles_exact_mut(&mut self, tile: NonZeroUsize) -> std::slice::ChunksExactMut<'_, T> {
+		self.as_mut_slice().chunks_exact_mut(tile.get())
+	}
+
+	/// Returns an iterator over `size` sized mutable subslices of the data, starting at the end.
+	///
+	/// This functions exactly as `slice::rchunks_mut`.
+	pub fn rchunks_mut(&mut self, size: NonZeroUsize) -> std::slice::RChunksMut<'_, T> {
+		self.as_mut_slice().rchunks_mut(size.get())
+	}
+
+	/// Returns an iterator over `size` sized mutable subslices of the data, starting at the end.
+	///
+	/// This functions exactly as `slice::rchunks_exact_mut`.
+	pub fn rchunks_exact_mut(&mut self, size: NonZeroUsize) -> std::slice::RChunksExactMut<'_, T> {
+		self.as_mut_slice().rchunks_exact_mut(size.get())
+	}
+
+	/// Borrows the unused capacity after `self.len()` elements as a different type.
+	///
+	/// The new borrow is properly aligned for `U` and its capacity is the number of `U`s that fit
+	/// into the remaining bytes. It does not overlap with the values already stored.
+	///
+	/// Panics if `size_of::<U>() == 0` or if not even one `U` fits.
+	pub fn reborrow_spare_as<U>(&mut self) -> ReusableMemoryBorrow<'_, U> {
+		assert_ne!(mem::size_of::<U>(), 0);
+
+		let spare_bytes = (self.capacity.get() - self.len) * mem::size_of::<T>();
+		let spare_ptr = unsafe { self.as_mut_ptr().add(self.len) as *mut u8 };
+
+		let align_offset = crate::base::align_offset(spare_ptr, mem::align_of::<U>());
+		let capacity = spare_bytes.saturating_sub(align_offset) / mem::size_of::<U>();
+		let capacity = NonZeroUsize::new(capacity).expect("Not enough spare capacity");
+
+		unsafe {
+			ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(spare_ptr.add(align_offset) as *mut U),
+				capacity
+			)
+		}
+	}
+
+	/// Moves the specified range into `out` and shifts the remaining items to close the gap.
+	///
+	/// This has the same effect as `out.extend(self.drain(range))`,
+	/// but the items are moved in bulk instead of one by one.
+	pub fn drain_into(&mut self, range: impl RangeBounds<usize>, out: &mut Vec<T>) {
+		let len = self.len();
+		let Range { start, end } = drain::bounded_range(range, len);
+		let count = end - start;
+
+		out.reserve(count);
+		unsafe {
+			ptr::copy_nonoverlapping(
+				self.as_ptr().add(start),
+				out.as_mut_ptr().add(out.len()),
+				count
+			);
+			out.set_len(out.len() + count);
+
+			ptr::copy(self.as_ptr().add(end), self.as_mut_ptr().add(start), len - end);
+			self.set_len(len - count);
+		}
+	}
+
+	/// Returns the layout of the borrowed memory for programmatic inspection.
+	pub fn dump_layout(&self) -> BorrowLayout {
+		BorrowLayout {
+			address: self.as_ptr() as usize,
+			len: self.len,
+			capacity: self.capacity.get(),
+			align: mem::align_of::<T>(),
+			byte_span: self.capacity.get() * mem::size_of::<T>()
+		}
+	}
+
+	/// Moves the stored values into an array if there are exactly `N` of them.
+	///
+	/// Returns `self` unchanged otherwise.
+	pub fn try_into_array<const N: usize>(self) -> Result<[T; N], Self> {
+		if self.len != N {
+			return Err(self)
+		}
+
+		let array = unsafe { ptr::read(self.as_ptr() as *const [T; N]) };
+		// The values were moved into `array`, don't drop them.
+		mem::forget(self);
+
+		Ok(array)
+	}
+
+	/// Retains only the elements for which `f` returns `true`, without preserving their order.
+	///
+	/// Each removed element is replaced by the last element, so every removal is O(1).
+	pub fn retain_unordered<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+		let mut index = 0;
+		while index < self.len {
+			if f(&self.as_slice()[index]) {
+				index += 1;
+				continue
+			}
+
+			unsafe {
+				// Shrink first so that a panicking drop can't cause a double drop.
+				self.len -= 1;
+				let removed = ptr::read(self.as_ptr().add(index));
+				ptr::copy(self.as_ptr().add(self.len), self.as_mut_ptr().add(index), 1);
+				drop(removed);
+			}
+		}
+	}
+
+	/// Sums the stored values.
+	pub fn sum<'a, S: std::iter::Sum<&'a T>>(&'a self) -> S { self.as_slice().iter().sum() }
+
+	/// Returns the minimum of the stored values, or `None` if `self.len() == 0`.
+	pub fn min(&self) -> Option<&T>
+	where
+		T: Ord
+	{
+		self.as_slice().iter().min()
+	}
+
+	/// Returns the maximum of the stored values, or `None` if `self.len() == 0`.
+	pub fn max(&self) -> Option<&T>
+	where
+		T: Ord
+	{
+		self.as_slice().iter().max()
+	}
+
+	/// Moves all elements from `vec` to the end of the borrow, leaving `vec` empty.
+	///
+	/// Returns an error and leaves both unchanged if there is not enough capacity.
+	pub fn append_vec(&mut self, vec: &mut Vec<T>) -> Result<(), ReusableMemoryBorrowError> {
+		if vec.len() > self.capacity.get() - self.len {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity))
+		}
+
+		unsafe {
+			ptr::copy_nonoverlapping(vec.as_ptr(), self.as_mut_ptr().add(self.len), vec.len());
+			self.len += vec.len();
+			// The elements were moved, `vec` must not drop them.
+			vec.set_len(0);
+		}
+
+		Ok(())
+	}
+
+	/// Clears `dst` and clones all stored values into it.
+	///
+	/// Returns an error and leaves `dst` empty if its capacity is less than `self.len()`.
+	/// If `clone` panics, the values already cloned into `dst` are dropped.
+	pub fn clone_into(
+		&self, dst: &mut ReusableMemoryBorrow<'_, T>
+	) -> Result<(), ReusableMemoryBorrowError>
+	where
+		T: Clone
+	{
+		dst.clear();
+		if self.len > dst.capacity.get() {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(dst.capacity))
+		}
+
+		// Clears the destination if `clone` panics.
+		struct ClearGuard<'a, 'b, T>(&'a mut ReusableMemoryBorrow<'b, T>);
+		impl<T> Drop for ClearGuard<'_, '_, T> {
+			fn drop(&mut self) { self.0.clear(); }
+		}
+
+		let guard = ClearGuard(dst);
+		for value in self.as_slice() {
+			guard.0.push(value.clone()).unwrap();
+		}
+		mem::forget(guard);
+
+		Ok(())
+	}
+
+	/// Removes and drops all values for which `f` returns `true`, keeping the order of the rest.
+	///
+	/// Returns the number of removed values.
+	pub fn remove_matching<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> usize {
+		let mut removed = 0;
+		self.compact(|_, value| !f(value), |value| {
+			removed += 1;
+			drop(value)
+		});
+
+		removed
+	}
+
+	/// Keeps the values for which `f` returns `true` in place, keeping their order,
+	/// and moves the rest into a new `Vec`, also in order.
+	///
+	/// If `f` panics, the values that were not processed yet are kept.
+	pub fn partition_off<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> Vec<T> {
+		let mut removed = Vec::new();
+		self.compact(|_, value| f(value), |value| removed.push(value));
+
+		removed
+	}
+
+	/// Removes consecutive values for which `same` returns `true`, keeping the first one.
+	///
+	/// `same(a, b)` is called with `a` being the current value and `b` the last kept value before it.
+	/// Both of them can be mutated, for example to merge `a` into `b`.
+	///
+	/// If `same` panics, the values that were not processed yet are kept.
+	pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same: F) {
+		self.compact(
+			|kept, value| match kept.last_mut() {
+				Some(last) => !same(value, last),
+				None => true
+			},
+			drop
+		);
+	}
+
+	/// Removes consecutive equal values, keeping the first one.
+	pub fn dedup(&mut self)
+	where
+		T: PartialEq
+	{
+		self.dedup_by(|a, b| a == b)
+	}
+
+	/// Removes consecutive equal values, keeping the first one, and returns each kept value
+	/// together with the number of values it replaced, including itself.
+	///
+	/// On sorted values, this is a run-length encoding.
+	pub fn dedup_with_count(&mut self) -> Vec<(T, usize)>
+	where
+		T: PartialEq + Clone
+	{
+		let mut counts: Vec<usize> = Vec::new();
+		self.compact(
+			|kept, value| match (kept.last(), counts.last_mut()) {
+				(Some(last), Some(count)) if last == value => {
+					*count += 1;
+					false
+				}
+				_ => {
+					counts.push(1);
+					true
+				}
+			},
+			drop
+		);
+
+		self.iter().cloned().zip(counts).collect()
+	}
+
+	/// Keeps only the values for which `f` returns `Ok(true)`, keeping their order.
+	///
+	/// Stops calling `f` at the first error and returns it. In that case, the values before
+	/// the one that errored are already retained and compacted, and the value that errored
+	/// and all the values after it are kept in their original order.
+	pub fn try_retain<E, F: FnMut(&T) -> Result<bool, E>>(&mut self, mut f: F) -> Result<(), E> {
+		let mut error = None;
+		self.compact(
+			|_, value| {
+				if error.is_some() {
+					return true
+				}
+
+				match f(value) {
+					Ok(keep) => keep,
+					Err(err) => {
+						error = Some(err);
+						true
+					}
+				}
+			},
+			drop
+		);
+
+		match error {
+			None => Ok(()),
+			Some(err) => Err(err)
+		}
+	}
+
+	/// Removes all duplicate values, not just consecutive ones, keeping the first one of each
+	/// and the order of the kept values.
+	///
+	/// This allocates a temporary `HashSet` of references to the values and the flags
+	/// of which values to keep. The values themselves are not cloned.
+	pub fn dedup_unsorted(&mut self)
+	where
+		T: Eq + Hash
+	{
+		// The set borrows the values, so decide what to keep before any of them moves.
+		let mut seen = HashSet::with_capacity(self.len);
+		let keep: Vec<bool> = self.iter().map(|value| seen.insert(value)).collect();
+		drop(seen);
+
+		let mut keep = keep.into_iter();
+		self.compact(|_, _| keep.next().unwrap(), drop);
+	}
+
+	/// Keeps the values for which `keep` returns `true`, shifting them to close the gaps,
+	/// and moves the other values into `remove`.
+	///
+	/// `keep` also receives the values kept so far.
+	///
+	/// If `keep` or `remove` panics, the values that were not processed yet are kept.
+	fn compact<K, R>(&mut self, mut keep: K, mut remove: R)
+	where
+		K: FnMut(&mut [T], &mut T) -> bool,
+		R: FnMut(T)
+	{
+		// Shifts the unprocessed values to close the gap and fixes the length, even on panic.
+		struct CompactGuard<'a, 'mem, T> {
+			borrow: &'a mut ReusableMemoryBorrow<'mem, T>,
+			original_len: usize,
+			processed: usize,
+			removed: usize
+		}
+		impl<T> Drop for CompactGuard<'_, '_, T> {
+			fn drop(&mut self) {
+				unsafe {
+					let ptr = self.borrow.as_mut_ptr();
+					ptr::copy(
+						ptr.add(self.processed),
+						ptr.add(self.processed - self.removed),
+						self.original_len - self.processed
+					);
+				}
+				self.borrow.len = self.original_len - self.removed;
+			}
+		}
+
+		let original_len = self.len;
+		// Rather leak than double-drop if the guard is leaked.
+		self.len = 0;
+
+		let mut guard = CompactGuard { borrow: self, original_len, processed: 0, removed: 0 };
+		while guard.processed < original_len {
+			let current = unsafe { guard.borrow.as_mut_ptr().add(guard.processed) };
+
+			let kept = unsafe {
+				std::slice::from_raw_parts_mut(
+					guard.borrow.as_mut_ptr(),
+					guard.processed - guard.removed
+				)
+			};
+			if keep(kept, unsafe { &mut *current }) {
+				if guard.removed > 0 {
+					unsafe {
+						ptr::copy_nonoverlapping(current, current.sub(guard.removed), 1);
+					}
+				}
+				guard.processed += 1;
+			} else {
+				guard.processed += 1;
+				guard.removed += 1;
+				remove(unsafe { ptr::read(current) });
+			}
+		}
+	}
+
+	/// Borrows the unused capacity after `self.len()` elements as an independent borrow.
+	///
+	/// The new borrow starts empty with capacity of `self.remaining_capacity()`.
+	/// Dropping it drops only the values pushed into it.
+	///
+	/// Panics if `self.remaining_capacity() == 0`.
+	pub fn spare_as_borrow(&mut self) -> ReusableMemoryBorrow<'_, T> { self.reborrow_spare_as::<T>() }
+
+	/// Clones the values in `src` range and appends them to the end.
+	///
+	/// Returns an error and leaves `self` unchanged if there is not enough capacity.
+	///
+	/// Panics if `src` is out of bounds.
+	pub fn extend_from_within<R: RangeBounds<usize>>(
+		&mut self, src: R
+	) -> Result<(), ReusableMemoryBorrowError>
+	where
+		T: Clone
+	{
+		let Range { start, end } = drain::bounded_range(src, self.len);
+		if end - start > self.remaining_capacity() {
+			return Err(ReusableMemoryBorrowError::NotEnoughCapacity(self.capacity))
+		}
+
+		for index in start .. end {
+			// The appended values never overlap with `src`, which is before `self.len()`.
+			let value = unsafe { &*self.as_ptr().add(index) }.clone();
+			self.push(value).unwrap();
+		}
+
+		Ok(())
+	}
+
+	/// Returns the unused capacity as a slice of uninitialized values.
+	///
+	/// After writing into the first `n` values, the length can be advanced by calling `set_len(len() + n)`.
+	pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+		unsafe {
+			std::slice::from_raw_parts_mut(
+				self.as_mut_ptr().add(self.len) as *mut MaybeUninit<T>,
+				self.remaining_capacity()
+			)
+		}
+	}
+
+	/// Passes a pointer to the unused capacity and its length to `f`
+	/// and advances the length by the number of values `f` returns.
+	///
+	/// The returned number is clamped to the remaining capacity.
+	///
+	/// ### Safety
+	///
+	/// * `f` must initialize at least as many values as it returns, starting at the pointer.
+	pub unsafe fn write_via<F: FnOnce(*mut T, usize) -> usize>(&mut self, f: F) {
+		let available = self.remaining_capacity();
+		let written = f(self.as_mut_ptr().add(self.len), available);
+
+		self.len += written.min(available);
+	}
+
+	/// Clones the stored values into a boxed slice.
+	///
+	/// This is the same as `self.to_vec().into_boxed_slice()`.
+	pub fn snapshot(&self) -> Box<[T]>
+	where
+		T: Clone
+	{
+		self.as_slice().to_vec().into_boxed_slice()
+	}
+
+	/// Sorts the stored values by a key computed at most once per value.
+	///
+	/// Delegates to `slice::sort_by_cached_key`, which allocates a temporary vec of the keys.
+	pub fn sort_by_cached_key<K: Ord, F: FnMut(&T) -> K>(&mut self, f: F) {
+		self.as_mut_slice().sort_by_cached_key(f)
+	}
+
+	/// Moves the value at `index` to the front, shifting the values before it back by one.
+	///
+	/// Panics if `index >= self.len()`.
+	pub fn move_to_front(&mut self, index: usize) {
+		assert!(index < self.len);
+
+		unsafe {
+			let ptr = self.as_mut_ptr();
+			let value = ptr::read(ptr.add(index));
+			ptr::copy(ptr, ptr.add(1), index);
+			ptr::write(ptr, value);
+		}
+	}
+
+	/// Splits the stored values into a prefix, `LANES`-sized chunks and a suffix, similar to `slice::as_simd`.
+	///
+	/// If `size_of::<[T; LANES]>()` is a power of two, the chunks are aligned to it, so they can be
+	/// loaded as SIMD vectors. The prefix and the suffix are shorter than `LANES`, unless the chunks
+	/// can't be aligned at all, in which case all the values are in the prefix.
+	///
+	/// Panics if `LANES == 0`.
+	pub fn as_simd_chunks<const LANES: usize>(&self) -> (&[T], &[[T; LANES]], &[T])
+	where
+		T: Copy
+	{
+		assert_ne!(LANES, 0);
+
+		let slice = self.as_slice();
+		let chunk_bytes = mem::size_of::<[T; LANES]>();
+		let prefix_len = if chunk_bytes.is_power_of_two() {
+			let offset = crate::base::align_offset(slice.as_ptr() as *const u8, chunk_bytes);
+			if offset % mem::size_of::<T>() == 0 { offset / mem::size_of::<T>() } else { slice.len() }
+		} else {
+			0
+		};
+
+		let (prefix, rest) = slice.split_at(prefix_len.min(slice.len()));
+		let chunk_count = rest.len() / LANES;
+		let (middle, suffix) = rest.split_at(chunk_count * LANES);
+		let middle =
+			unsafe { std::slice::from_raw_parts(middle.as_ptr() as *const [T; LANES], chunk_count) };
+
+		(prefix, middle, suffix)
+	}
+
+	/// Pushes values returned by `f` until the borrow is full.
+	///
+	/// Unlike `resize_with`, this never shrinks the borrow and never fails.
+	pub fn fill_spare_with<F: FnMut() -> T>(&mut self, mut f: F) {
+		while self.len < self.capacity.get() {
+			unsafe {
+				ptr::write(self.as_mut_ptr().add(self.len), f());
+			}
+			self.len += 1;
+		}
+	}
+
+	/// Binary searches the sorted values with a comparator function, same as `slice::binary_search_by`.
+	pub fn binary_search_by<F: FnMut(&T) -> std::cmp::Ordering>(&self, f: F) -> Result<usize, usize> {
+		self.as_slice().binary_search_by(f)
+	}
+
+	/// Binary searches the values sorted by a key, same as `slice::binary_search_by_key`.
+	pub fn binary_search_by_key<K: Ord, F: FnMut(&T) -> K>(&self, key: &K, f: F) -> Result<usize, usize> {
+		self.as_slice().binary_search_by_key(key, f)
+	}
+
+	/// Returns the addresses of the bytes used by the stored values.
+	///
+	/// This can be used to check that borrows do not alias.
+	pub fn byte_range(&self) -> Range<usize> {
+		let address = self.as_ptr() as usize;
+
+		address .. address + self.len * mem::size_of::<T>()
+	}
+
+	/// Splits the stored values into `parts` disjoint slices of nearly equal length
+	/// and calls `f` on each of them in order.
+	///
+	/// The first `len() % parts` slices are one value longer than the rest.
+	/// If there are fewer values than `parts`, the last slices are empty.
+	pub fn for_each_split_mut<F: FnMut(&mut [T])>(&mut self, parts: NonZeroUsize, mut f: F) {
+		let short_len = self.len / parts.get();
+		let long_count = self.len % parts.get();
+
+		let mut rest = self.as_mut_slice();
+		for index in 0 .. parts.get() {
+			let part_len = if index < long_count { short_len + 1 } else { short_len };
+			let (part, tail) = rest.split_at_mut(part_len);
+			f(part);
+			rest = tail;
+		}
+	}
+
+	/// Truncates to `new_len` values and returns the unused capacity, which now includes
+	/// the slots of the dropped values, as a slice of uninitialized values.
+	///
+	/// See [`truncate`](#method.truncate) and [`spare_capacity_mut`](#method.spare_capacity_mut).
+	pub fn truncate_returning_spare(&mut self, new_len: usize) -> &mut [MaybeUninit<T>] {
+		self.truncate(new_len);
+
+		self.spare_capacity_mut()
+	}
+
+	/// Returns the first `N` values as an array, or `None` if there are fewer than `N` values.
+	pub fn first_chunk<const N: usize>(&self) -> Option<&[T; N]> { self.as_slice().first_chunk() }
+
+	/// Returns the first `N` values as a mutable array, or `None` if there are fewer than `N` values.
+	pub fn first_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+		self.as_mut_slice().first_chunk_mut()
+	}
+
+	/// Returns the last `N` values as an array, or `None` if there are fewer than `N` values.
+	pub fn last_chunk<const N: usize>(&self) -> Option<&[T; N]> { self.as_slice().last_chunk() }
+
+	/// Returns the last `N` values as a mutable array, or `None` if there are fewer than `N` values.
+	pub fn last_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+		self.as_mut_slice().last_chunk_mut()
+	}
+
+	/// Converts the borrow into an iterator yielding the stored values from back to front.
+	///
+	/// The values that are not yielded are dropped together with the iterator.
+	pub fn into_iter_rev(mut self) -> impl Iterator<Item = T> + 'mem {
+		std::iter::from_fn(move || self.pop())
+	}
+
+	/// Returns the stored values as `Cow::Borrowed`, which clones them only if it is made owned.
+	pub fn as_cow(&self) -> std::borrow::Cow<'_, [T]>
+	where
+		T: Clone
+	{
+		std::borrow::Cow::Borrowed(self.as_slice())
+	}
+
+	/// Returns an iterator over the indices and the stored values.
+	pub fn enumerate(&self) -> std::iter::Enumerate<std::slice::Iter<'_, T>> {
+		self.as_slice().iter().enumerate()
+	}
+
+	/// Converts the borrow into a slice of the stored values that lives as long as the borrowed memory.
+	///
+	/// The stored values are never dropped. The memory stays borrowed while the slice is alive:
+	///
+	/// ```compile_fail
+	/// # use std::num::NonZeroUsize;
+	/// let mut memory: reusable_memory::ReusableMemory = reusable_memory::ReusableMemory::new();
+	/// let mut borrow = memory.borrow_mut_as::<u32>(NonZeroUsize::new(1).unwrap());
+	/// borrow.push(1).unwrap();
+	///
+	/// let slice = borrow.into_slice();
+	/// let _ = memory.borrow_mut_as::<u32>(NonZeroUsize::new(1).unwrap());
+	/// assert_eq!(slice, &[1]);
+	/// ```
+	pub fn into_slice(self) -> &'mem [T] {
+		let slice = unsafe { std::slice::from_raw_parts(self.as_ptr(), self.len) };
+		mem::forget(self);
+
+		slice
+	}
 }
 impl<'mem, T> Deref for ReusableMemoryBorrow<'mem, T> {
 	type Target = [T];
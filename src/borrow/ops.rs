@@ -0,0 +1,73 @@
+//! Differential testing of borrow operations against `Vec`.
+//!
+//! Enabled by the `arbitrary` feature, which also derives `arbitrary::Arbitrary` for [`BorrowOp`]
+//! so that operation sequences can be generated by a fuzzer.
+
+use std::{fmt::Debug, num::NonZeroUsize};
+
+use crate::ReusableMemory;
+
+/// Operation on a `ReusableMemoryBorrow`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum BorrowOp<T> {
+	Push(T),
+	Pop,
+	/// Inserts `value` at `index`, clamped to the length of the borrow.
+	Insert { index: usize, value: T },
+	/// Removes the value at `index`, clamped to the last index. Does nothing if the borrow is empty.
+	Remove(usize),
+	/// Drains `start .. end`, clamped to the length of the borrow.
+	Drain { start: usize, end: usize },
+	Clear
+}
+
+/// Applies `ops` to a borrow with `capacity` and to a `Vec` model of it.
+///
+/// Panics if the borrow and the model diverge at any point.
+pub fn apply_ops<T: Clone + PartialEq + Debug>(capacity: NonZeroUsize, ops: &[BorrowOp<T>]) {
+	let mut memory: ReusableMemory = ReusableMemory::new();
+	let mut borrow = memory.borrow_mut_as::<T>(capacity);
+	let mut model: Vec<T> = Vec::with_capacity(capacity.get());
+
+	for op in ops {
+		match op {
+			BorrowOp::Push(value) => {
+				let pushed = borrow.push(value.clone()).is_ok();
+				assert_eq!(pushed, model.len() < capacity.get());
+				if pushed {
+					model.push(value.clone());
+				}
+			}
+			BorrowOp::Pop => assert_eq!(borrow.pop(), model.pop()),
+			BorrowOp::Insert { index, value } => {
+				let index = (*index).min(model.len());
+
+				let inserted = borrow.insert(index, value.clone()).is_ok();
+				assert_eq!(inserted, model.len() < capacity.get());
+				if inserted {
+					model.insert(index, value.clone());
+				}
+			}
+			&BorrowOp::Remove(index) => {
+				if !model.is_empty() {
+					let index = index.min(model.len() - 1);
+
+					assert_eq!(borrow.remove(index), model.remove(index));
+				}
+			}
+			&BorrowOp::Drain { start, end } => {
+				let start = start.min(model.len());
+				let end = end.clamp(start, model.len());
+
+				assert!(borrow.drain(start .. end).eq(model.drain(start .. end)));
+			}
+			BorrowOp::Clear => {
+				borrow.clear();
+				model.clear();
+			}
+		}
+
+		assert_eq!(borrow.as_slice(), model.as_slice());
+	}
+}
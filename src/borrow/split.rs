@@ -0,0 +1,103 @@
+use std::{marker::PhantomData, mem, num::NonZeroUsize, ptr};
+
+use super::{ReusableMemoryBorrow, ReusableMemoryBorrowError};
+
+/// Borrow of the reusable memory that can also lend the rest of the reserved memory as another type.
+///
+/// Created by `ReusableMemory::split_scratch`. The first borrow has the requested capacity,
+/// the reserved memory after its capacity can be borrowed with [`split`](#method.split)
+/// while the first borrow stays usable.
+pub struct SplitScratch<'mem, T> {
+	first: ReusableMemoryBorrow<'mem, T>,
+	/// Start of the first borrow.
+	///
+	/// The first borrow can be swapped for a different one through `first`, so the bounds
+	/// are recorded here and never derived from it.
+	start: *mut u8,
+	/// End of the capacity of the first borrow.
+	spare_start: *mut u8,
+	/// End of the reserved memory.
+	end: *mut u8,
+
+	// The spare memory is borrowed for `'mem` as well.
+	boo: PhantomData<&'mem mut [u8]>
+}
+impl<'mem, T> SplitScratch<'mem, T> {
+	/// Constructs the split from the first borrow and the end of the reserved memory.
+	///
+	/// ### Safety
+	///
+	/// * The memory between the end of the capacity of `first` and `end` must be borrowed for `'mem`.
+	pub(crate) unsafe fn new(first: ReusableMemoryBorrow<'mem, T>, end: *mut u8) -> Self {
+		let start = first.memory.as_ptr() as *mut u8;
+		let spare_start = start.add(first.capacity().get() * mem::size_of::<T>());
+
+		SplitScratch { first, start, spare_start, end, boo: PhantomData }
+	}
+
+	/// Returns the first borrow.
+	pub fn first(&mut self) -> &mut ReusableMemoryBorrow<'mem, T> { &mut self.first }
+
+	/// Returns the number of reserved bytes after the capacity of the first borrow.
+	pub fn spare_bytes(&self) -> usize { self.end as usize - self.spare_start as usize }
+
+	/// Returns the first borrow and borrows the memory after its capacity as a different type.
+	///
+	/// The second borrow is properly aligned for `U` and its capacity is the number of `U`s
+	/// that fit into the spare memory. The borrows do not overlap.
+	///
+	/// Panics if `size_of::<U>() == 0`, if not even one `U` fits
+	/// or if the first borrow was swapped for a different one.
+	pub fn split<U>(&mut self) -> (&mut ReusableMemoryBorrow<'mem, T>, ReusableMemoryBorrow<'_, U>) {
+		assert_ne!(mem::size_of::<U>(), 0);
+		self.assert_first();
+
+		let spare_ptr = self.spare_start;
+		let align_offset = crate::base::align_offset(spare_ptr, mem::align_of::<U>());
+		let capacity = self.spare_bytes().saturating_sub(align_offset) / mem::size_of::<U>();
+		let capacity = NonZeroUsize::new(capacity).expect("Not enough spare memory");
+
+		let second = unsafe {
+			ReusableMemoryBorrow::from_raw_parts(
+				ptr::NonNull::new_unchecked(spare_ptr.add(align_offset) as *mut U),
+				capacity
+			)
+		};
+
+		(&mut self.first, second)
+	}
+
+	/// Grows the capacity of the first borrow to `new_capacity` using the spare memory,
+	/// keeping its values.
+	///
+	/// Does nothing if `new_capacity` is not greater than the current capacity.
+	/// Returns an error with the current capacity and leaves the borrow unchanged
+	/// if there is not enough spare memory.
+	///
+	/// Panics if the first borrow was swapped for a different one.
+	pub fn grow(&mut self, new_capacity: NonZeroUsize) -> Result<(), ReusableMemoryBorrowError> {
+		self.assert_first();
+
+		let capacity = self.first.capacity();
+		if new_capacity <= capacity {
+			return Ok(())
+		}
+
+		let needed_bytes = match (new_capacity.get() - capacity.get()).checked_mul(mem::size_of::<T>()) {
+			Some(needed_bytes) if needed_bytes <= self.spare_bytes() => needed_bytes,
+			_ => return Err(ReusableMemoryBorrowError::NotEnoughCapacity(capacity))
+		};
+		self.first.capacity = new_capacity;
+		self.spare_start = unsafe { self.spare_start.add(needed_bytes) };
+
+		Ok(())
+	}
+
+	fn assert_first(&self) {
+		assert_eq!(
+			self.first.memory.as_ptr() as *mut u8,
+			self.start,
+			"The first borrow was swapped for a different one"
+		);
+	}
+}
@@ -0,0 +1,61 @@
+use std::{
+	mem,
+	ops::{Deref, DerefMut}
+};
+
+use super::ReusableMemoryBorrow;
+
+/// Borrow tracking state of `ReusableMemory`.
+#[derive(Debug)]
+pub(crate) struct BorrowTracking {
+	/// Highest number of bytes used by a dropped tracked borrow.
+	pub high_water_bytes: usize,
+	/// Whether a tracked borrow is alive.
+	pub borrowed: bool
+}
+impl BorrowTracking {
+	pub const fn new() -> Self { BorrowTracking { high_water_bytes: 0, borrowed: false } }
+}
+/// A clone is never borrowed.
+impl Clone for BorrowTracking {
+	fn clone(&self) -> Self {
+		BorrowTracking { high_water_bytes: self.high_water_bytes, ..BorrowTracking::new() }
+	}
+}
+
+/// Borrow of the reusable memory that records its used bytes when dropped.
+///
+/// Created by `ReusableMemory::borrow_mut_as_tracked`. Dereferences to the underlying borrow.
+/// When dropped, `len() * size_of::<T>()` is recorded as the high-water mark of the memory
+/// if it is higher than the previous one, and the memory stops being flagged as borrowed.
+///
+/// If the borrow is leaked instead of dropped, the memory stays flagged as borrowed
+/// until `ReusableMemory::reset_tracking` is called.
+pub struct TrackedBorrow<'mem, T> {
+	borrow: ReusableMemoryBorrow<'mem, T>,
+	tracking: &'mem mut BorrowTracking
+}
+impl<'mem, T> TrackedBorrow<'mem, T> {
+	pub(crate) fn new(
+		borrow: ReusableMemoryBorrow<'mem, T>, tracking: &'mem mut BorrowTracking
+	) -> Self {
+		tracking.borrowed = true;
+
+		TrackedBorrow { borrow, tracking }
+	}
+}
+impl<'mem, T> Deref for TrackedBorrow<'mem, T> {
+	type Target = ReusableMemoryBorrow<'mem, T>;
+
+	fn deref(&self) -> &Self::Target { &self.borrow }
+}
+impl<'mem, T> DerefMut for TrackedBorrow<'mem, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target { &mut self.borrow }
+}
+impl<T> Drop for TrackedBorrow<'_, T> {
+	fn drop(&mut self) {
+		let used_bytes = self.borrow.len() * mem::size_of::<T>();
+		self.tracking.high_water_bytes = self.tracking.high_water_bytes.max(used_bytes);
+		self.tracking.borrowed = false;
+	}
+}
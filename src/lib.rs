@@ -24,14 +24,14 @@
 //! }
 //! // The borrowed memory is automatically returned when the object is dropped, and the pushed values are dropped as well.
 //! 
-//! // Now the memory can be reused, even as multiple different types (current limit is 5 because the code is generated by a macro):
+//! // Now the memory can be reused, even as multiple different types (up to 5 with `borrow_mut_X_as`, up to 8 with `borrow_mut_tuple_as`):
 //! {
 //! 	let (mut borrow_t, mut borrow_u) = memory.borrow_mut_two_as::<usize, u8>(
 //! 		[
 //! 			std::num::NonZeroUsize::new(1).unwrap(),
 //! 			std::num::NonZeroUsize::new(2).unwrap()
 //! 		]
-//! 	);
+//! 	).unwrap();
 //! 
 //! 	borrow_t.push(0usize).unwrap();
 //! 	
@@ -44,17 +44,41 @@
 //! }
 //! ```
 
+#![allow(clippy::tabs_in_doc_comments)]
+
 mod base;
 pub mod borrow;
+mod pool;
+mod tuple;
 
 pub use base::*;
+pub use pool::*;
+pub use tuple::BorrowLayoutList;
 
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants, clippy::useless_conversion, static_mut_refs)]
 mod tests {
-	use std::num::NonZeroUsize;
+	use std::{cell::Cell, num::NonZeroUsize};
 
 	use super::{borrow::*, *};
 
+	/// Counts live instances in `counter` to check that values are dropped exactly once.
+	#[derive(Debug)]
+	struct DropCounter<'c> {
+		counter: &'c Cell<usize>,
+		value: u8
+	}
+	impl<'c> DropCounter<'c> {
+		pub fn new(counter: &'c Cell<usize>, value: u8) -> Self {
+			counter.set(counter.get() + 1);
+
+			DropCounter { counter, value }
+		}
+	}
+	impl Drop for DropCounter<'_> {
+		fn drop(&mut self) { self.counter.set(self.counter.get() - 1); }
+	}
+
 	/// Tests borrow of `u8` from base of `u8`.
 	#[test]
 	fn same_type() {
@@ -85,7 +109,7 @@ mod tests {
 
 	/// Tests borrow of `usize` from base of `u8`.
 	///
-	/// This fails on Miri because it cannot align the pointers (yet?)
+	/// Under Miri, `align_offset` may give up, in which case the address based fallback is used.
 	#[test]
 	fn different_align() {
 		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
@@ -106,7 +130,7 @@ mod tests {
 			let (mut borrow_a, mut borrow_b) = rm.borrow_mut_two_as::<u16, i16>([
 				NonZeroUsize::new(6).unwrap(),
 				NonZeroUsize::new(3).unwrap()
-			]);
+			]).unwrap();
 
 			borrow_a.push(1).unwrap();
 			borrow_a.push(2).unwrap();
@@ -126,7 +150,7 @@ mod tests {
 
 	/// Tests borrow of `u64`,`u32` and `u16` from base of `u8`.
 	///
-	/// This fails on Miri because it cannot align the pointers (yet?)
+	/// Under Miri, `align_offset` may give up, in which case the address based fallback is used.
 	#[test]
 	fn borrow_three_different_align() {
 		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
@@ -136,7 +160,7 @@ mod tests {
 					NonZeroUsize::new(1).unwrap(),
 					NonZeroUsize::new(2).unwrap(),
 					NonZeroUsize::new(4).unwrap()
-				]);
+				]).unwrap();
 
 			borrow_u64.push(1).unwrap();
 
@@ -158,6 +182,28 @@ mod tests {
 		}
 	}
 
+	/// Tests that `try_borrow_mut_as` only succeeds with enough reserved memory.
+	#[test]
+	fn try_borrow_reserved() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = NonZeroUsize::new(4).unwrap();
+		let needed_length = rm.needed_capacity_for::<u32>(capacity);
+
+		match rm.try_borrow_mut_as::<u32>(capacity) {
+			Err(ReusableMemoryBorrowError::NotEnoughReservedMemory(n)) if n == needed_length => (),
+			_ => panic!("Expected Err(ReusableMemoryBorrowError::NotEnoughReservedMemory)")
+		}
+
+		rm.reserve(needed_length);
+		{
+			let mut borrow = rm.try_borrow_mut_as::<u32>(capacity).unwrap();
+			borrow.push_from_exact_iter(0 .. 4).unwrap();
+
+			assert_eq!(borrow.as_ptr().align_offset(std::mem::align_of::<u32>()), 0);
+			assert_eq!(borrow.as_slice(), &[0, 1, 2, 3]);
+		}
+	}
+
 	#[test]
 	fn push_iter() {
 		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
@@ -368,4 +414,2115 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn tiles_mut() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(9).unwrap());
+			borrow.push_from_exact_iter(0 .. 9).unwrap();
+
+			let mut tiles = borrow.tiles_mut(NonZeroUsize::new(4).unwrap());
+			assert_eq!(tiles.next(), Some(&mut [0, 1, 2, 3][..]));
+			assert_eq!(tiles.next(), Some(&mut [4, 5, 6, 7][..]));
+			assert_eq!(tiles.next(), Some(&mut [8][..]));
+			assert_eq!(tiles.next(), None);
+		}
+	}
+
+	/// Tests that only the capacity is compared, not the contents.
+	#[test]
+	fn eq_capacity_only() {
+		use std::{
+			collections::hash_map::DefaultHasher,
+			hash::{Hash, Hasher}
+		};
+
+		let mut rm_a: ReusableMemory<u8> = ReusableMemory::with_capacity(16);
+		let mut rm_b: ReusableMemory<u8> = ReusableMemory::with_capacity(16);
+		{
+			let capacity = NonZeroUsize::new(16).unwrap();
+			rm_a.borrow_mut_as::<u8>(capacity).push_from_exact_iter(0 .. 16).unwrap();
+			rm_b.borrow_mut_as::<u8>(capacity).push_from_exact_iter(16 .. 32).unwrap();
+		}
+
+		assert_eq!(rm_a, rm_b);
+
+		let hash = |rm: &ReusableMemory<u8>| {
+			let mut hasher = DefaultHasher::new();
+			rm.hash(&mut hasher);
+			hasher.finish()
+		};
+		assert_eq!(hash(&rm_a), hash(&rm_b));
+
+		assert_ne!(rm_a, ReusableMemory::with_capacity(32));
+	}
+
+	#[test]
+	fn drain_remaining_mut() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(5).unwrap());
+			borrow.push_from_exact_iter(0 ..= 4).unwrap();
+
+			{
+				let mut drain = borrow.drain(1 ..= 3);
+				assert_eq!(drain.next(), Some(1));
+				assert_eq!(drain.remaining_mut(), &[2, 3]);
+
+				drain.remaining_mut().iter_mut().for_each(|value| *value *= 10);
+				assert_eq!(drain.next(), Some(20));
+				assert_eq!(drain.next(), Some(30));
+				assert_eq!(drain.remaining_mut(), &[]);
+			}
+
+			assert_eq!(borrow.as_slice(), &[0, 4]);
+		}
+	}
+
+	/// Tests that values over capacity are dropped exactly once.
+	#[test]
+	fn extend_truncating() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(3).unwrap());
+
+			let iter = (0 .. 5).map(|value| DropCounter::new(&counter, value));
+
+			assert_eq!(borrow.extend_truncating(iter), 3);
+			assert_eq!(counter.get(), 3);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 2]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn borrow_raw() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let raw_start;
+		{
+			let raw = rm.borrow_raw_mut_as::<u32>(NonZeroUsize::new(3).unwrap());
+			raw_start = raw.as_ptr() as usize;
+			assert_eq!(raw.as_ptr().align_offset(std::mem::align_of::<u32>()), 0);
+			assert_eq!(raw.len(), 3);
+
+			for (index, slot) in raw.iter_mut().enumerate() {
+				*slot = std::mem::MaybeUninit::new(index as u32 * 2);
+			}
+			let values: Vec<u32> = raw.iter().map(|slot| unsafe { slot.assume_init() }).collect();
+			assert_eq!(values, &[0, 2, 4]);
+		}
+
+		// The slice lies within the reserved memory.
+		let backing_start = rm.backing_ptr() as usize;
+		assert!(raw_start >= backing_start);
+		assert!(raw_start + 3 * std::mem::size_of::<u32>() <= backing_start + rm.capacity());
+	}
+
+	#[test]
+	fn reborrow_spare() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+			borrow.push(1).unwrap();
+			borrow.push(u32::MAX).unwrap();
+
+			let head_end = unsafe { borrow.as_ptr().add(borrow.len()) } as usize;
+			{
+				let mut spare = borrow.reborrow_spare_as::<u16>();
+				assert_eq!(spare.as_ptr().align_offset(std::mem::align_of::<u16>()), 0);
+				assert!(spare.as_ptr() as usize >= head_end);
+				assert_eq!(spare.capacity().get(), 4);
+
+				spare.push_from_exact_iter(0 .. 4).unwrap();
+				assert_eq!(spare.as_slice(), &[0, 1, 2, 3]);
+			}
+
+			assert_eq!(borrow.as_slice(), &[1, u32::MAX]);
+		}
+	}
+
+	#[test]
+	fn push_all() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(3).unwrap());
+
+			borrow.push_all(vec![1, 2]).unwrap();
+			assert_eq!(borrow.as_slice(), &[1, 2]);
+
+			match borrow.push_all(vec![3, 4]) {
+				Err(ReusableMemoryBorrowError::NotEnoughCapacity(c)) if c.get() == 3 => (),
+				_ => panic!("Expected Err(ReusableMemoryBorrowError::NotEnoughCapacity)")
+			}
+			assert_eq!(borrow.as_slice(), &[1, 2]);
+		}
+	}
+
+	/// Tests that an iterator reporting a huge length does not overflow the capacity check.
+	#[test]
+	fn push_exact_iter_len_overflow() {
+		struct HugeIter;
+		impl Iterator for HugeIter {
+			type Item = u8;
+
+			fn next(&mut self) -> Option<u8> { Some(0) }
+
+			fn size_hint(&self) -> (usize, Option<usize>) { (usize::MAX, Some(usize::MAX)) }
+		}
+		impl ExactSizeIterator for HugeIter {}
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(2).unwrap());
+			borrow.push(1).unwrap();
+
+			assert!(borrow.push_from_exact_iter(HugeIter).is_err());
+			assert_eq!(borrow.as_slice(), &[1]);
+		}
+	}
+
+	#[test]
+	fn drain_into() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(6).unwrap());
+			borrow.push_from_exact_iter(0 .. 6).unwrap();
+
+			let mut out = vec![10, 11];
+			borrow.drain_into(2 .. 4, &mut out);
+
+			assert_eq!(out, &[10, 11, 2, 3]);
+			assert_eq!(borrow.as_slice(), &[0, 1, 4, 5]);
+		}
+	}
+
+	/// Tests that the reported layout matches the actual borrows.
+	#[test]
+	fn layout_for_two() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = [NonZeroUsize::new(3).unwrap(), NonZeroUsize::new(2).unwrap()];
+
+		let layout = rm.layout_for_two::<u8, u64>(capacity);
+		assert_eq!(layout, [(0, 3), (8, 2)]);
+		{
+			let (borrow_u8, borrow_u64) = rm.borrow_mut_two_as::<u8, u64>(capacity).unwrap();
+			assert_eq!(borrow_u64.as_ptr().align_offset(std::mem::align_of::<u64>()), 0);
+
+			let start = borrow_u8.as_ptr() as usize;
+			assert_eq!(borrow_u8.as_ptr() as usize - start, layout[0].0);
+			assert_eq!(borrow_u64.as_ptr() as usize - start, layout[1].0);
+			assert_eq!(borrow_u8.capacity().get(), layout[0].1);
+			assert_eq!(borrow_u64.capacity().get(), layout[1].1);
+		}
+	}
+
+	#[test]
+	fn dump_layout() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(3).unwrap());
+			borrow.push(1).unwrap();
+
+			let layout = borrow.dump_layout();
+			assert_eq!(layout.address, borrow.as_ptr() as usize);
+			assert_eq!(layout.address % layout.align, 0);
+			assert_eq!(borrow.as_ptr().align_offset(layout.align), 0);
+			assert_eq!(layout.len, 1);
+			assert_eq!(layout.capacity, 3);
+			assert_eq!(layout.byte_span, 12);
+		}
+	}
+
+	#[test]
+	fn try_into_array() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(4).unwrap());
+			let iter = (0 .. 3).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			let borrow = borrow.try_into_array::<2>().unwrap_err();
+			assert_eq!(borrow.len(), 3);
+			assert_eq!(counter.get(), 3);
+
+			let array = borrow.try_into_array::<3>().unwrap();
+			assert_eq!(array.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 2]);
+			assert_eq!(counter.get(), 3);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn push_if_fits() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(2).unwrap());
+
+			assert!(borrow.push_if_fits(DropCounter::new(&counter, 1)));
+			assert!(borrow.push_if_fits(DropCounter::new(&counter, 2)));
+			assert!(!borrow.push_if_fits(DropCounter::new(&counter, 3)));
+			assert_eq!(counter.get(), 2);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[1, 2]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests that borrowing within the reserved memory does not reallocate.
+	#[test]
+	fn backing_ptr_stable() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			rm.borrow_mut_as::<u32>(NonZeroUsize::new(8).unwrap()).push(1).unwrap();
+		}
+		let backing_ptr = rm.backing_ptr();
+		{
+			rm.borrow_mut_as::<u16>(NonZeroUsize::new(8).unwrap()).push(1).unwrap();
+		}
+
+		assert_eq!(rm.backing_ptr(), backing_ptr);
+	}
+
+	#[test]
+	fn collect_exact() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let borrow = rm.collect_exact(vec![1u32, 2, 3].into_iter());
+			assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+			assert_eq!(borrow.capacity().get(), 3);
+		}
+		{
+			let borrow = rm.collect_exact(Vec::<u32>::new().into_iter());
+			assert!(borrow.is_empty());
+			assert_eq!(borrow.capacity().get(), 1);
+		}
+	}
+
+	#[test]
+	fn retain_unordered() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(6).unwrap());
+			let iter = (0 .. 6).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.retain_unordered(|c| c.value % 3 != 0);
+			assert_eq!(counter.get(), 4);
+
+			let mut values: Vec<u8> = borrow.iter().map(|c| c.value).collect();
+			values.sort();
+			assert_eq!(values, &[1, 2, 4, 5]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn peek() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(2).unwrap());
+			assert_eq!(borrow.peek(), None);
+			assert_eq!(borrow.peek_mut(), None);
+
+			borrow.push(1).unwrap();
+			borrow.push(2).unwrap();
+			assert_eq!(borrow.peek(), Some(&2));
+
+			*borrow.peek_mut().unwrap() = 3;
+			assert_eq!(borrow.pop(), Some(3));
+			assert_eq!(borrow.peek(), Some(&1));
+		}
+	}
+
+	/// Tests that moving the memory keeps the reserved allocation.
+	#[test]
+	fn move_memory() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap()).push(1).unwrap();
+		}
+		let backing_ptr = rm.backing_ptr();
+
+		let mut moved = Box::new(rm);
+		assert_eq!(moved.backing_ptr(), backing_ptr);
+		{
+			let borrow = moved.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+			assert_eq!(borrow.as_ptr() as *const u8, backing_ptr);
+		}
+	}
+
+	/// Tests that elements skipped by `nth` are dropped exactly once.
+	#[test]
+	fn drain_nth() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(6).unwrap());
+			let iter = (0 .. 6).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			{
+				let mut drain = borrow.drain(1 .. 5);
+				let third = drain.nth(2).unwrap();
+				assert_eq!(third.value, 3);
+				assert_eq!(counter.get(), 4);
+
+				assert!(drain.nth(5).is_none());
+				assert_eq!(counter.get(), 3);
+			}
+
+			assert_eq!(counter.get(), 2);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 5]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn reductions() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+			assert_eq!(borrow.sum::<u32>(), 0);
+			assert_eq!(borrow.max(), None);
+
+			borrow.push_all(vec![3, 7, 1, 5]).unwrap();
+			assert_eq!(borrow.sum::<u32>(), 16);
+			assert_eq!(borrow.min(), Some(&1));
+			assert_eq!(borrow.max(), Some(&7));
+		}
+	}
+
+	/// Tests backfilling a length prefix after writing the payload.
+	#[test]
+	fn cursor_write_at() {
+		use std::io::Write;
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(8).unwrap());
+			{
+				let mut cursor = borrow.cursor();
+				cursor.write_all(&[0, 0]).unwrap();
+				cursor.write_all(b"hello").unwrap();
+
+				let payload_len = (cursor.position() - 2) as u16;
+				cursor.write_at(0, &payload_len.to_be_bytes());
+				assert_eq!(cursor.position(), 7);
+
+				cursor.seek(2);
+				cursor.write_all(b"J").unwrap();
+				assert!(cursor.write_all(b"ello!!").is_err());
+			}
+
+			assert_eq!(borrow.as_slice(), b"\x00\x05Jello!");
+		}
+	}
+
+	#[test]
+	#[should_panic]
+	fn cursor_write_at_out_of_range() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(8).unwrap());
+		borrow.push(0).unwrap();
+
+		borrow.cursor().write_at(0, &[1, 2]);
+	}
+
+	#[test]
+	fn drain_peek() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(5).unwrap());
+			borrow.push_from_exact_iter(0 ..= 4).unwrap();
+
+			{
+				let mut peek = borrow.drain_peek(1 ..= 3);
+				assert_eq!(peek.next(), Some(&1));
+				assert_eq!(peek.next_back(), Some(&3));
+			}
+
+			assert_eq!(borrow.as_slice(), &[0, 1, 2, 3, 4]);
+		}
+	}
+
+	#[test]
+	fn stats() {
+		let mut rm: ReusableMemory<u32> = ReusableMemory::new();
+		rm.reserve(10);
+
+		let stats = rm.stats();
+		assert_eq!(stats.element_size, 4);
+		assert_eq!(stats.capacity_elements, rm.capacity());
+		assert_eq!(stats.reserved_bytes, rm.capacity() * 4);
+		assert!(stats.reserved_bytes >= 40);
+	}
+
+	#[test]
+	fn try_borrow_n() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+
+		assert!(rm.try_borrow_mut_as_n::<u32>(0).is_none());
+		{
+			let borrow = rm.try_borrow_mut_as_n::<u32>(3).unwrap();
+			assert_eq!(borrow.capacity().get(), 3);
+		}
+	}
+
+	#[test]
+	fn drain_as_mut_slice() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(6).unwrap());
+			borrow.push_from_exact_iter(0 .. 6).unwrap();
+
+			let mut drain = borrow.drain(1 .. 5);
+			assert_eq!(drain.next(), Some(1));
+			assert_eq!(drain.next_back(), Some(4));
+			assert_eq!(drain.as_slice(), &[2, 3]);
+
+			drain.as_mut_slice()[1] = 30;
+			assert_eq!(drain.as_slice(), &[2, 30]);
+			assert_eq!(drain.collect::<Vec<_>>(), &[2, 30]);
+		}
+	}
+
+	#[test]
+	fn apply_ops() {
+		use super::borrow::ops::{apply_ops, BorrowOp};
+
+		apply_ops(
+			NonZeroUsize::new(4).unwrap(),
+			&[
+				BorrowOp::Push(1u32),
+				BorrowOp::Push(2),
+				BorrowOp::Push(3),
+				BorrowOp::Drain { start: 1, end: 2 },
+				BorrowOp::Push(4),
+				BorrowOp::Push(5),
+				BorrowOp::Push(6),
+				BorrowOp::Pop,
+				BorrowOp::Drain { start: 2, end: 10 },
+				BorrowOp::Insert { index: 0, value: 8 },
+				BorrowOp::Insert { index: 10, value: 9 },
+				BorrowOp::Insert { index: 1, value: 10 },
+				BorrowOp::Insert { index: 1, value: 11 },
+				BorrowOp::Remove(1),
+				BorrowOp::Remove(10),
+				BorrowOp::Clear,
+				BorrowOp::Pop,
+				BorrowOp::Remove(0),
+				BorrowOp::Push(7)
+			]
+		);
+	}
+
+	#[test]
+	fn drain_owned() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+			borrow.push_all(vec![1, 2, 3]).unwrap();
+
+			assert_eq!(borrow.drain_owned().collect::<Vec<_>>(), &[1, 2, 3]);
+			assert!(borrow.is_empty());
+		}
+		let (backing_ptr, capacity) = (rm.backing_ptr(), rm.capacity());
+		{
+			let mut borrow = rm.borrow_mut_as::<u16>(NonZeroUsize::new(8).unwrap());
+			borrow.push(1).unwrap();
+		}
+
+		assert_eq!(rm.backing_ptr(), backing_ptr);
+		assert_eq!(rm.capacity(), capacity);
+	}
+
+	/// Tests mutation through `as_mut_slice`, meant to be run under Miri with strict provenance.
+	#[test]
+	fn as_mut_slice() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(3).unwrap());
+			borrow.push_all(vec![1, 2, 3]).unwrap();
+
+			borrow.as_mut_slice().iter_mut().for_each(|value| *value *= 2);
+			assert_eq!(borrow.as_slice(), &[2, 4, 6]);
+		}
+	}
+
+	#[test]
+	fn append_vec() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(3).unwrap());
+			borrow.push(DropCounter::new(&counter, 0)).unwrap();
+
+			let mut vec: Vec<_> = (1 .. 3).map(|value| DropCounter::new(&counter, value)).collect();
+			borrow.append_vec(&mut vec).unwrap();
+			assert!(vec.is_empty());
+			assert_eq!(counter.get(), 3);
+
+			vec.push(DropCounter::new(&counter, 3));
+			assert!(borrow.append_vec(&mut vec).is_err());
+			assert_eq!(vec.len(), 1);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 2]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn padding_overhead_for_three() {
+		let rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = [
+			NonZeroUsize::new(3).unwrap(),
+			NonZeroUsize::new(2).unwrap(),
+			NonZeroUsize::new(1).unwrap()
+		];
+
+		// `u8`s take bytes 0 .. 3, `u64`s are aligned to 8 and take bytes 8 .. 24, `u16` follows.
+		assert_eq!(rm.padding_overhead_for_three::<u8, u64, u16>(capacity), 5);
+		assert_eq!(rm.padding_overhead_for_three::<u64, u16, u8>(capacity), 0);
+	}
+
+	#[test]
+	fn split_scratch() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		let backing_end = rm.backing_ptr() as usize + rm.capacity();
+		{
+			let mut scratch = rm.split_scratch::<u32>(NonZeroUsize::new(4).unwrap());
+			scratch.first().push_all(vec![1, 2]).unwrap();
+
+			let (first, mut second) = scratch.split::<u16>();
+			first.push_all(vec![3, 4]).unwrap();
+			second.push_from_iter(0 ..).unwrap_err();
+
+			let first_end = unsafe { first.as_ptr().add(first.capacity().get()) } as usize;
+			let second_end = unsafe { second.as_ptr().add(second.capacity().get()) } as usize;
+			assert!(second.as_ptr() as usize >= first_end);
+			assert!(second_end <= backing_end);
+			assert_eq!(second.as_ptr().align_offset(std::mem::align_of::<u16>()), 0);
+
+			assert_eq!(first.as_slice(), &[1, 2, 3, 4]);
+			assert_eq!(second.len(), second.capacity().get());
+			assert!(second.iter().copied().eq(0 .. second.len() as u16));
+		}
+	}
+
+	#[test]
+	fn clone_into() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let (mut src, mut dst, mut small) = rm.borrow_mut_three_as::<String, String, String>([
+			NonZeroUsize::new(2).unwrap(),
+			NonZeroUsize::new(2).unwrap(),
+			NonZeroUsize::new(1).unwrap()
+		]).unwrap();
+		src.push("hello".to_string()).unwrap();
+		src.push("world".to_string()).unwrap();
+		dst.push("stale".to_string()).unwrap();
+
+		src.clone_into(&mut dst).unwrap();
+		dst[0].push('!');
+		assert_eq!(src.as_slice(), &["hello", "world"]);
+		assert_eq!(dst.as_slice(), &["hello!", "world"]);
+
+		small.push("stale".to_string()).unwrap();
+		match src.clone_into(&mut small) {
+			Err(ReusableMemoryBorrowError::NotEnoughCapacity(c)) if c.get() == 1 => (),
+			_ => panic!("Expected Err(ReusableMemoryBorrowError::NotEnoughCapacity)")
+		}
+		assert!(small.is_empty());
+	}
+
+	/// Tests that values cloned before a panic are dropped.
+	#[test]
+	fn clone_into_panic() {
+		struct PanicClone<'c>(DropCounter<'c>);
+		impl Clone for PanicClone<'_> {
+			fn clone(&self) -> Self {
+				assert_ne!(self.0.value, 1);
+				PanicClone(DropCounter::new(self.0.counter, self.0.value))
+			}
+		}
+
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let (mut src, mut dst) = rm.borrow_mut_two_as::<PanicClone, PanicClone>([
+				NonZeroUsize::new(2).unwrap(),
+				NonZeroUsize::new(2).unwrap()
+			]).unwrap();
+			src.push(PanicClone(DropCounter::new(&counter, 0))).unwrap();
+			src.push(PanicClone(DropCounter::new(&counter, 1))).unwrap();
+
+			let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+				src.clone_into(&mut dst).unwrap();
+			}));
+			assert!(result.is_err());
+			assert!(dst.is_empty());
+			assert_eq!(counter.get(), 2);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn pop_front() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(3).unwrap());
+			let iter = (0 .. 3).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			assert_eq!(borrow.pop_front().map(|c| c.value), Some(0));
+			assert_eq!(counter.get(), 2);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[1, 2]);
+
+			assert_eq!(borrow.pop_front().map(|c| c.value), Some(1));
+			assert_eq!(borrow.pop_front().map(|c| c.value), Some(2));
+			assert!(borrow.pop_front().is_none());
+			assert_eq!(counter.get(), 0);
+		}
+	}
+
+	#[test]
+	fn remove_matching() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(7).unwrap());
+			borrow.push_all(vec![0, 1, 0, 0, 2, 3, 0]).unwrap();
+
+			assert_eq!(borrow.remove_matching(|&value| value == 0), 4);
+			assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+		}
+	}
+
+	/// Tests that a panicking predicate keeps the unprocessed values.
+	#[test]
+	fn remove_matching_panic() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(5).unwrap());
+			let iter = (0 .. 5).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+				borrow.remove_matching(|c| {
+					assert_ne!(c.value, 3);
+					c.value == 1
+				})
+			}));
+			assert!(result.is_err());
+			assert_eq!(counter.get(), 4);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 2, 3, 4]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests that setting a length over capacity panics in debug builds.
+	#[test]
+	#[should_panic]
+	#[cfg(debug_assertions)]
+	fn set_len_over_capacity() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(2).unwrap());
+
+		unsafe {
+			borrow.set_len(3);
+		}
+		let _ = borrow.as_slice();
+	}
+
+	#[test]
+	fn spare_as_borrow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(5).unwrap());
+			borrow.push_all(vec![1, 2]).unwrap();
+			assert_eq!(borrow.remaining_capacity(), 3);
+
+			let prefix_end = unsafe { borrow.as_ptr().add(borrow.len()) };
+			{
+				let mut spare = borrow.spare_as_borrow();
+				assert_eq!(spare.as_ptr(), prefix_end);
+				assert_eq!(spare.capacity().get(), 3);
+
+				spare.push_all(vec![3, 4, 5]).unwrap();
+				assert!(spare.push(6).is_err());
+			}
+
+			assert_eq!(borrow.as_slice(), &[1, 2]);
+			borrow.push(3).unwrap();
+			assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+		}
+	}
+
+	#[test]
+	fn drain_tail() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(5).unwrap());
+			borrow.push_from_exact_iter(0 .. 5).unwrap();
+
+			assert_eq!(borrow.drain_tail(2).collect::<Vec<_>>(), &[2, 3, 4]);
+			assert_eq!(borrow.as_slice(), &[0, 1]);
+		}
+	}
+
+	#[test]
+	fn capacity_usize() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(7).unwrap());
+
+		assert_eq!(borrow.capacity_usize(), borrow.capacity().get());
+		assert_eq!(borrow.capacity_usize(), 7);
+	}
+
+	#[test]
+	fn cursor_read() {
+		use std::io::Read;
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(16).unwrap());
+			borrow.push_all(b"hello, world".iter().copied()).unwrap();
+
+			let mut cursor = borrow.cursor();
+			let mut content = Vec::new();
+			let mut chunk = [0u8; 5];
+			loop {
+				match cursor.read(&mut chunk).unwrap() {
+					0 => break,
+					count => content.extend_from_slice(&chunk[.. count])
+				}
+			}
+
+			assert_eq!(content, b"hello, world");
+			assert_eq!(cursor.read(&mut chunk).unwrap(), 0);
+		}
+	}
+
+	#[test]
+	fn extend_from_within() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(5).unwrap());
+			borrow.push("a".to_string()).unwrap();
+			borrow.push("b".to_string()).unwrap();
+			borrow.push("c".to_string()).unwrap();
+
+			borrow.extend_from_within(.. 2).unwrap();
+			assert_eq!(borrow.as_slice(), &["a", "b", "c", "a", "b"]);
+
+			match borrow.extend_from_within(1 ..= 1) {
+				Err(ReusableMemoryBorrowError::NotEnoughCapacity(c)) if c.get() == 5 => (),
+				_ => panic!("Expected Err(ReusableMemoryBorrowError::NotEnoughCapacity)")
+			}
+			assert_eq!(borrow.as_slice(), &["a", "b", "c", "a", "b"]);
+		}
+	}
+
+	/// `ReusableMemory<B>` must stay covariant in `B`, same as `Vec<B>`. This only needs to compile.
+	#[test]
+	fn memory_covariant() {
+		fn shorten<'a>(memory: ReusableMemory<&'static str>) -> ReusableMemory<&'a str> { memory }
+
+		let memory: ReusableMemory<&'static str> = ReusableMemory::new();
+		assert_eq!(shorten(memory).capacity(), 0);
+	}
+
+	#[test]
+	fn push_array() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let (mut borrow_a, mut borrow_b) = rm.borrow_mut_two_as::<u32, u32>([
+				NonZeroUsize::new(3).unwrap(),
+				NonZeroUsize::new(2).unwrap()
+			]).unwrap();
+
+			borrow_a.push_array([1, 2, 3]).unwrap();
+			assert_eq!(borrow_a.as_slice(), &[1, 2, 3]);
+
+			match borrow_b.push_array([1, 2, 3]) {
+				Err(([1, 2, 3], ReusableMemoryBorrowError::NotEnoughCapacity(c))) if c.get() == 2 => (),
+				_ => panic!("Expected Err(([1, 2, 3], ReusableMemoryBorrowError::NotEnoughCapacity))")
+			}
+			assert!(borrow_b.is_empty());
+		}
+	}
+
+	/// Tests dropping a partially consumed drain of values without drop glue.
+	#[test]
+	fn drain_drop_pod() {
+		use std::mem::ManuallyDrop;
+
+		let counter = Cell::new(0);
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let new = |value| ManuallyDrop::new(DropCounter::new(&counter, value));
+			let mut borrow = rm.borrow_mut_as::<ManuallyDrop<DropCounter>>(NonZeroUsize::new(6).unwrap());
+			borrow.push_from_exact_iter((0 .. 6).map(new)).unwrap();
+
+			{
+				let mut drain = borrow.drain(1 .. 5);
+				assert_eq!(drain.next().map(|value| value.value), Some(1));
+			}
+			// `ManuallyDrop` has no drop glue, so no destructors ran.
+			assert_eq!(counter.get(), 6);
+			assert_eq!(borrow.iter().map(|value| value.value).collect::<Vec<_>>(), &[0, 5]);
+
+			// The drained memory can be reused.
+			borrow.push_from_exact_iter((6 .. 10).map(new)).unwrap();
+			let values = borrow.iter().map(|value| value.value).collect::<Vec<_>>();
+			assert_eq!(values, &[0, 5, 6, 7, 8, 9]);
+
+			borrow.iter_mut().for_each(|value| unsafe { ManuallyDrop::drop(value) });
+		}
+		// Only the drained values were never dropped.
+		assert_eq!(counter.get(), 4);
+	}
+
+	/// Tests that dropping a partially consumed drain drops each remaining value.
+	#[test]
+	fn drain_drop_loop() {
+		let counter = Cell::new(0);
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(6).unwrap());
+			borrow.push_from_exact_iter((0 .. 6).map(|value| DropCounter::new(&counter, value))).unwrap();
+
+			{
+				let mut drain = borrow.drain(1 .. 5);
+				assert_eq!(drain.next().map(|value| value.value), Some(1));
+				assert_eq!(counter.get(), 5);
+			}
+			assert_eq!(counter.get(), 2);
+			assert_eq!(borrow.iter().map(|value| value.value).collect::<Vec<_>>(), &[0, 5]);
+		}
+		assert_eq!(counter.get(), 0);
+	}
+
+	#[test]
+	fn borrow_reporting() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = NonZeroUsize::new(4).unwrap();
+
+		assert!(rm.borrow_mut_as_reporting::<u32>(capacity).1);
+		assert!(!rm.borrow_mut_as_reporting::<u32>(capacity).1);
+	}
+
+	#[test]
+	fn rchunks_mut() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(7).unwrap());
+			borrow.push_from_exact_iter(0 .. 7).unwrap();
+			let size = NonZeroUsize::new(3).unwrap();
+
+			let mut chunks = borrow.rchunks_mut(size);
+			assert_eq!(chunks.next(), Some(&mut [4, 5, 6][..]));
+			assert_eq!(chunks.next(), Some(&mut [1, 2, 3][..]));
+			assert_eq!(chunks.next(), Some(&mut [0][..]));
+
+			let mut chunks = borrow.rchunks_exact_mut(size);
+			assert_eq!(chunks.next(), Some(&mut [4, 5, 6][..]));
+			assert_eq!(chunks.next(), Some(&mut [1, 2, 3][..]));
+			assert_eq!(chunks.next(), None);
+			assert_eq!(chunks.into_remainder(), &[0]);
+		}
+	}
+
+	#[test]
+	fn write_via() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(5).unwrap());
+			borrow.push(0).unwrap();
+
+			unsafe {
+				borrow.write_via(|ptr, available| {
+					assert_eq!(available, 4);
+					for index in 0 .. 3 {
+						ptr.add(index).write(index as u32 + 1);
+					}
+
+					3
+				});
+			}
+
+			assert_eq!(borrow.as_slice(), &[0, 1, 2, 3]);
+		}
+	}
+
+	/// Tests that memory returned to the pool is reused.
+	#[test]
+	fn pool_reuse() {
+		let pool: ReusableMemoryPool<u8> = ReusableMemoryPool::with_capacity(1, 16);
+		let backing_ptr = {
+			let mut memory = pool.acquire();
+			assert_eq!(pool.available(), 0);
+
+			memory.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap()).push(1).unwrap();
+			memory.backing_ptr()
+		};
+		assert_eq!(pool.available(), 1);
+
+		let memory = pool.acquire();
+		assert_eq!(memory.backing_ptr(), backing_ptr);
+		assert!(memory.capacity() >= 16);
+	}
+
+	#[test]
+	fn drain_rev_into() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let (mut src, mut dst) = rm.borrow_mut_two_as::<u8, u8>([
+			NonZeroUsize::new(4).unwrap(),
+			NonZeroUsize::new(3).unwrap()
+		]).unwrap();
+		src.push_all(vec![1, 2, 3, 4]).unwrap();
+
+		assert!(src.drain_rev_into(.., &mut dst).is_err());
+		assert_eq!(src.as_slice(), &[1, 2, 3, 4]);
+		assert!(dst.is_empty());
+
+		src.drain_rev_into(.. 3, &mut dst).unwrap();
+		assert_eq!(src.as_slice(), &[4]);
+		assert_eq!(dst.as_slice(), &[3, 2, 1]);
+	}
+
+	#[test]
+	fn extend_bounded() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(3).unwrap());
+
+			let mut leftover = borrow.extend_bounded(0 .. 5).unwrap_err();
+			assert_eq!(borrow.as_slice(), &[0, 1, 2]);
+			assert_eq!(leftover.next(), Some(3));
+			assert_eq!(leftover.next(), Some(4));
+			assert_eq!(leftover.next(), None);
+		}
+	}
+
+	/// Tests that `partition_off` moves the rejected values into the vec without double drops.
+	#[test]
+	fn partition_off() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(6).unwrap());
+			let iter = (0 .. 6).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			let removed = borrow.partition_off(|c| c.value % 2 == 0);
+			assert_eq!(counter.get(), 6);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 2, 4]);
+			assert_eq!(removed.iter().map(|c| c.value).collect::<Vec<_>>(), &[1, 3, 5]);
+
+			std::mem::drop(removed);
+			assert_eq!(counter.get(), 3);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests that `borrow_mut_tuple_as` borrows aligned blocks of two types that do not overlap.
+	#[test]
+	fn borrow_tuple_two() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let (mut borrow_u8, mut borrow_u64) = rm.borrow_mut_tuple_as::<(u8, u64)>([
+				NonZeroUsize::new(3).unwrap(),
+				NonZeroUsize::new(2).unwrap()
+			]).unwrap();
+
+			borrow_u8.push_all(vec![1, 2, 3]).unwrap();
+			borrow_u64.push_all(vec![u64::MAX, 4]).unwrap();
+
+			assert_eq!(borrow_u64.as_ptr().align_offset(std::mem::align_of::<u64>()), 0);
+			assert!(borrow_u8.as_ptr() as usize + 3 <= borrow_u64.as_ptr() as usize);
+
+			assert_eq!(borrow_u8.as_slice(), &[1, 2, 3]);
+			assert_eq!(borrow_u64.as_slice(), &[u64::MAX, 4]);
+		}
+	}
+
+	/// Tests that `borrow_mut_tuple_as` supports more types than the `borrow_mut_X_as` methods.
+	#[test]
+	fn borrow_tuple_six() {
+		let mut rm: ReusableMemory<u16> = ReusableMemory::new();
+		{
+			let capacity = [NonZeroUsize::new(3).unwrap(); 6];
+			let (mut b_u8, mut b_u64, mut b_u16, mut b_u32, mut b_u128, mut b_char) =
+				rm.borrow_mut_tuple_as::<(u8, u64, u16, u32, u128, char)>(capacity).unwrap();
+
+			b_u8.push_all(vec![1, 2, 3]).unwrap();
+			b_u64.push_all(vec![4, 5, 6]).unwrap();
+			b_u16.push_all(vec![7, 8, 9]).unwrap();
+			b_u32.push_all(vec![10, 11, 12]).unwrap();
+			b_u128.push_all(vec![13, 14, 15]).unwrap();
+			b_char.push_all(vec!['a', 'b', 'c']).unwrap();
+
+			let mut blocks = [
+				(b_u8.as_ptr() as usize, 3, std::mem::align_of::<u8>()),
+				(b_u64.as_ptr() as usize, 3 * 8, std::mem::align_of::<u64>()),
+				(b_u16.as_ptr() as usize, 3 * 2, std::mem::align_of::<u16>()),
+				(b_u32.as_ptr() as usize, 3 * 4, std::mem::align_of::<u32>()),
+				(b_u128.as_ptr() as usize, 3 * 16, std::mem::align_of::<u128>()),
+				(b_char.as_ptr() as usize, 3 * 4, std::mem::align_of::<char>())
+			];
+			for &(address, _, align) in blocks.iter() {
+				assert_eq!(address % align, 0);
+			}
+			blocks.sort_unstable();
+			for pair in blocks.windows(2) {
+				assert!(pair[0].0 + pair[0].1 <= pair[1].0);
+			}
+
+			assert_eq!(b_u8.as_slice(), &[1, 2, 3]);
+			assert_eq!(b_u64.as_slice(), &[4, 5, 6]);
+			assert_eq!(b_u16.as_slice(), &[7, 8, 9]);
+			assert_eq!(b_u32.as_slice(), &[10, 11, 12]);
+			assert_eq!(b_u128.as_slice(), &[13, 14, 15]);
+			assert_eq!(b_char.as_slice(), &['a', 'b', 'c']);
+		}
+	}
+
+	/// Tests that `dedup_by` merges consecutive values with the same key into the first one.
+	#[test]
+	fn dedup_by() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<(u8, DropCounter)>(NonZeroUsize::new(6).unwrap());
+			let iter = [(1, 1), (1, 2), (2, 3), (3, 4), (3, 5), (3, 6)]
+				.iter()
+				.map(|&(key, value)| (key, DropCounter::new(&counter, value)));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.dedup_by(|a, b| {
+				if a.0 == b.0 {
+					b.1.value += a.1.value;
+					true
+				} else {
+					false
+				}
+			});
+
+			assert_eq!(counter.get(), 3);
+			assert_eq!(
+				borrow.iter().map(|(key, c)| (*key, c.value)).collect::<Vec<_>>(),
+				&[(1, 3), (2, 3), (3, 15)]
+			);
+		}
+
+		assert_eq!(counter.get(), 0);
+
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(6).unwrap());
+		borrow.push_all(vec![1, 1, 2, 1, 1, 1]).unwrap();
+		borrow.dedup();
+		assert_eq!(borrow.as_slice(), &[1, 2, 1]);
+	}
+
+	/// Tests that dropping a tracked borrow records the used bytes.
+	#[test]
+	fn high_water_bytes() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		assert_eq!(rm.high_water_bytes(), 0);
+
+		{
+			let mut borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(1024).unwrap());
+			borrow.push_from_exact_iter(0 .. 1000).unwrap();
+		}
+		assert_eq!(rm.high_water_bytes(), 4000);
+
+		{
+			let mut borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(1024).unwrap());
+			borrow.push(1).unwrap();
+		}
+		assert_eq!(rm.high_water_bytes(), 4000);
+	}
+
+	/// Tests that `snapshot` copies the stored values.
+	#[test]
+	fn snapshot() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+		borrow.push_all(vec![1, 2, 3]).unwrap();
+
+		let snapshot = borrow.snapshot();
+		borrow.clear();
+
+		assert_eq!(&*snapshot, &[1, 2, 3]);
+	}
+
+	/// Tests writing a borrow through `write_vectored`.
+	#[test]
+	fn as_io_slice() {
+		use std::io::Write;
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(8).unwrap());
+		borrow.push_all(b"world".to_vec()).unwrap();
+
+		let mut output = Vec::new();
+		let written = output
+			.write_vectored(&[std::io::IoSlice::new(b"hello "), borrow.as_io_slice()])
+			.unwrap();
+
+		assert_eq!(written, 11);
+		assert_eq!(output, b"hello world");
+	}
+
+	/// Tests that all the dropping paths drop the values back-to-front.
+	#[test]
+	fn drop_order() {
+		struct DropOrder<'o>(&'o std::cell::RefCell<Vec<u8>>, u8);
+		impl Drop for DropOrder<'_> {
+			fn drop(&mut self) { self.0.borrow_mut().push(self.1); }
+		}
+
+		let order = std::cell::RefCell::new(Vec::new());
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = NonZeroUsize::new(5).unwrap();
+
+		let mut borrow = rm.borrow_mut_as::<DropOrder>(capacity);
+		borrow.push_from_exact_iter((0 .. 5).map(|value| DropOrder(&order, value))).unwrap();
+		borrow.truncate(2);
+		assert_eq!(order.replace(Vec::new()), &[4, 3, 2]);
+		borrow.clear();
+		assert_eq!(order.replace(Vec::new()), &[1, 0]);
+
+		borrow.push_from_exact_iter((0 .. 5).map(|value| DropOrder(&order, value))).unwrap();
+		let mut drain = borrow.drain(1 .. 4);
+		std::mem::drop(drain.next());
+		std::mem::drop(drain);
+		assert_eq!(order.replace(Vec::new()), &[1, 3, 2]);
+
+		std::mem::drop(borrow);
+		assert_eq!(order.replace(Vec::new()), &[4, 0]);
+	}
+
+	/// Tests that `borrow_mut_raw` returns a pointer aligned for the layout.
+	#[test]
+	fn borrow_mut_raw() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let layout = std::alloc::Layout::from_size_align(64, 16).unwrap();
+
+		unsafe {
+			let ptr = rm.borrow_mut_raw(layout);
+			assert_eq!(ptr.as_ptr() as usize % 16, 0);
+
+			std::ptr::write_bytes(ptr.as_ptr(), 0xAB, 64);
+			assert_eq!(*ptr.as_ptr().add(63), 0xAB);
+		}
+		assert!(rm.capacity() >= 64);
+	}
+
+	/// Tests initializing the spare capacity before calling `set_len`, meant to be run under Miri.
+	#[test]
+	fn spare_capacity_set_len() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(4).unwrap());
+		borrow.push("a".to_string()).unwrap();
+
+		let spare = borrow.spare_capacity_mut();
+		assert_eq!(spare.len(), 3);
+		spare[0].write("b".to_string());
+		spare[1].write("c".to_string());
+		unsafe {
+			borrow.set_len(3);
+		}
+
+		assert_eq!(borrow.as_slice(), &["a", "b", "c"]);
+		assert_eq!(borrow.pop().as_deref(), Some("c"));
+	}
+
+	/// Tests that `borrow_mut_as_with_spare` reports the `T`s that still fit into the reserved memory.
+	#[test]
+	fn borrow_with_spare() {
+		let mut rm: ReusableMemory<u32> = ReusableMemory::with_capacity(16);
+		let capacity = rm.capacity();
+
+		{
+			let (borrow, spare) = rm.borrow_mut_as_with_spare::<u32>(NonZeroUsize::new(4).unwrap());
+			assert_eq!(borrow.capacity_usize(), 4);
+			assert_eq!(spare, capacity - 4);
+		}
+
+		let (_, spare) = rm.borrow_mut_as_with_spare::<u32>(NonZeroUsize::new(capacity).unwrap());
+		assert_eq!(spare, 0);
+		assert_eq!(rm.capacity(), capacity);
+	}
+
+	/// Tests sorting by a computed key.
+	#[test]
+	fn sort_by_cached_key() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<(String, u32)>(NonZeroUsize::new(4).unwrap());
+		borrow
+			.push_all(vec![
+				("ccc".to_string(), 1),
+				("a".to_string(), 2),
+				("dddd".to_string(), 3),
+				("bb".to_string(), 4)
+			])
+			.unwrap();
+
+		let mut calls = 0;
+		borrow.sort_by_cached_key(|(name, _)| {
+			calls += 1;
+			name.len()
+		});
+
+		assert_eq!(calls, 4);
+		assert_eq!(borrow.iter().map(|&(_, value)| value).collect::<Vec<_>>(), &[2, 4, 1, 3]);
+	}
+
+	/// Tests moving a value to the front.
+	#[test]
+	fn move_to_front() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(5).unwrap());
+		borrow.push_all(vec![0, 1, 2, 3, 4]).unwrap();
+
+		borrow.move_to_front(2);
+		assert_eq!(borrow.as_slice(), &[2, 0, 1, 3, 4]);
+
+		borrow.move_to_front(0);
+		assert_eq!(borrow.as_slice(), &[2, 0, 1, 3, 4]);
+	}
+
+	/// Tests that `try_with_capacity` returns an error on overflow.
+	#[test]
+	fn try_with_capacity() {
+		assert!(ReusableMemory::<u64>::try_with_capacity(usize::MAX / 4).is_err());
+
+		let rm = ReusableMemory::<u64>::try_with_capacity(16).unwrap();
+		assert!(rm.capacity() >= 16);
+	}
+
+	/// Tests splitting a borrow into aligned chunks.
+	#[test]
+	fn as_simd_chunks() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(16).unwrap());
+		borrow.push_from_exact_iter(0 .. 11).unwrap();
+
+		let (prefix, middle, suffix) = borrow.as_simd_chunks::<4>();
+		assert!(prefix.len() < 4);
+		assert!(suffix.len() < 4);
+		assert_eq!(middle.as_ptr() as usize % 16, 0);
+		assert_eq!(prefix.len() + middle.len() * 4 + suffix.len(), 11);
+
+		let joined: Vec<u32> =
+			prefix.iter().chain(middle.iter().flatten()).chain(suffix.iter()).copied().collect();
+		assert_eq!(joined, (0 .. 11).collect::<Vec<_>>());
+	}
+
+	/// Tests filling the rest of a half-full borrow.
+	#[test]
+	fn fill_spare_with() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u16>(NonZeroUsize::new(6).unwrap());
+		borrow.push_all(vec![1, 2, 3]).unwrap();
+
+		let mut next = 10;
+		borrow.fill_spare_with(|| {
+			next += 1;
+			next
+		});
+
+		assert_eq!(borrow.len(), borrow.capacity_usize());
+		assert_eq!(borrow.as_slice(), &[1, 2, 3, 11, 12, 13]);
+	}
+
+	/// Tests searching sorted records by a key.
+	#[test]
+	fn binary_search_by_key() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<(u32, &str)>(NonZeroUsize::new(4).unwrap());
+		borrow.push_all(vec![(1, "one"), (3, "three"), (5, "five"), (8, "eight")]).unwrap();
+
+		assert_eq!(borrow.binary_search_by_key(&5, |&(key, _)| key), Ok(2));
+		assert_eq!(borrow.binary_search_by_key(&4, |&(key, _)| key), Err(2));
+		assert_eq!(borrow.binary_search_by(|&(key, _)| key.cmp(&8)), Ok(3));
+		assert_eq!(borrow.binary_search_by(|&(key, _)| key.cmp(&0)), Err(0));
+	}
+
+	/// Tests that `borrow_mut_as_reusing` doesn't reallocate after a larger borrow of another type.
+	#[test]
+	fn borrow_mut_as_reusing() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as_reusing::<[u64; 4]>(NonZeroUsize::new(8).unwrap());
+			borrow.push([1; 4]).unwrap();
+		}
+		let capacity = rm.capacity();
+		let backing_ptr = rm.backing_ptr();
+
+		{
+			let mut borrow = rm.borrow_mut_as_reusing::<u32>(NonZeroUsize::new(64).unwrap());
+			borrow.push_from_exact_iter(0 .. 64).unwrap();
+			assert_eq!(borrow.as_ptr() as usize % std::mem::align_of::<u32>(), 0);
+		}
+		assert_eq!(rm.capacity(), capacity);
+		assert_eq!(rm.backing_ptr(), backing_ptr);
+	}
+
+	/// Tests that `swap_remove_front` moves the last value to the front.
+	#[test]
+	fn swap_remove_front() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(4).unwrap());
+		borrow.push_all(vec!["a".to_string(), "b".to_string(), "c".to_string()]).unwrap();
+
+		assert_eq!(borrow.swap_remove_front().as_deref(), Some("a"));
+		assert_eq!(borrow.as_slice(), &["c", "b"]);
+		assert_eq!(borrow.swap_remove_front().as_deref(), Some("c"));
+		assert_eq!(borrow.swap_remove_front().as_deref(), Some("b"));
+		assert_eq!(borrow.swap_remove_front(), None);
+	}
+
+	/// Tests that `dedup_with_count` returns the run lengths.
+	#[test]
+	fn dedup_with_count() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(6).unwrap());
+		borrow.push_all(vec![1, 1, 2, 3, 3, 3]).unwrap();
+
+		assert_eq!(borrow.dedup_with_count(), &[(1, 2), (2, 1), (3, 3)]);
+		assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+	}
+
+	/// Tests that `release` and `replace_vec` replace the reserved memory.
+	#[test]
+	fn release() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		rm.replace_vec(vec![1, 2, 3, 4]);
+		assert_eq!(rm.capacity(), 4);
+
+		rm.release();
+		assert_eq!(rm.capacity(), 0);
+	}
+
+	/// Tests that the replaced memory is poisoned in debug builds.
+	#[test]
+	#[cfg(debug_assertions)]
+	fn poison() {
+		let mut rm: ReusableMemory<u16> = ReusableMemory::with_capacity(8);
+		rm.borrow_mut_as::<u16>(NonZeroUsize::new(8).unwrap()).push_all(vec![1; 8]).unwrap();
+
+		let old = rm.replace_vec(Vec::new());
+		assert!(old.capacity() >= 8);
+
+		let bytes = unsafe { std::slice::from_raw_parts(old.as_ptr() as *const u8, old.capacity() * 2) };
+		assert!(bytes.iter().all(|&byte| byte == 0xDE));
+	}
+
+	/// Tests appending zero bytes.
+	#[test]
+	fn push_zeroed() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(102).unwrap());
+		borrow.push(1).unwrap();
+
+		borrow.push_zeroed(100).unwrap();
+		assert_eq!(borrow.len(), 101);
+		assert_eq!(borrow[0], 1);
+		assert!(borrow[1 ..].iter().all(|&byte| byte == 0));
+
+		assert!(borrow.push_zeroed(2).is_err());
+		assert_eq!(borrow.len(), 101);
+	}
+
+	/// Tests that the byte ranges of two borrows are disjoint.
+	#[test]
+	fn byte_range() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let (mut borrow_t, mut borrow_u) = rm.borrow_mut_two_as::<u16, u64>([
+			NonZeroUsize::new(3).unwrap(),
+			NonZeroUsize::new(2).unwrap()
+		]).unwrap();
+		borrow_t.push_all(vec![1, 2, 3]).unwrap();
+		borrow_u.push_all(vec![4, 5]).unwrap();
+
+		let range_t = borrow_t.byte_range();
+		let range_u = borrow_u.byte_range();
+		assert_eq!(range_t.len(), 6);
+		assert_eq!(range_u.len(), 16);
+		assert!(range_t.end <= range_u.start || range_u.end <= range_t.start);
+	}
+
+	/// Tests the state of the borrow after `try_retain` errors.
+	#[test]
+	fn try_retain() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(6).unwrap());
+		borrow.push_all(vec![1, 2, 3, 4, 5, 6]).unwrap();
+
+		assert_eq!(borrow.try_retain(|&value| Ok::<_, ()>(value != 4)), Ok(()));
+		assert_eq!(borrow.as_slice(), &[1, 2, 3, 5, 6]);
+
+		let mut calls = 0;
+		let result = borrow.try_retain(|&value| {
+			calls += 1;
+			match value {
+				3 => Err("invalid"),
+				value => Ok(value % 2 == 1)
+			}
+		});
+
+		assert_eq!(result, Err("invalid"));
+		assert_eq!(calls, 3);
+		assert_eq!(borrow.as_slice(), &[1, 3, 5, 6]);
+	}
+
+	/// Tests borrowing with a const capacity.
+	#[test]
+	fn borrow_mut_as_n() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as_n::<u32, 4>();
+		assert_eq!(borrow.capacity_usize(), 4);
+
+		borrow.push_all(vec![1, 2, 3, 4]).unwrap();
+		assert!(borrow.push(5).is_err());
+		assert_eq!(borrow.as_slice(), &[1, 2, 3, 4]);
+	}
+
+	/// Tests splitting 10 values into 3 parts.
+	#[test]
+	fn for_each_split_mut() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(10).unwrap());
+		borrow.push_from_exact_iter(0 .. 10).unwrap();
+
+		let mut parts = Vec::new();
+		let mut index = 0;
+		borrow.for_each_split_mut(NonZeroUsize::new(3).unwrap(), |part| {
+			parts.push(part.to_vec());
+			for value in part.iter_mut() {
+				*value += index * 100;
+			}
+			index += 1;
+		});
+
+		assert_eq!(parts, vec![vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+		assert_eq!(borrow.as_slice(), &[0, 1, 2, 3, 104, 105, 106, 207, 208, 209]);
+	}
+
+	/// Tests converting a vec into reusable memory.
+	#[test]
+	fn try_from_vec() {
+		use std::convert::TryFrom;
+
+		let mut vec: Vec<u8> = Vec::with_capacity(32);
+		vec.extend_from_slice(&[1, 2, 3]);
+		let capacity = vec.capacity();
+
+		let rm = ReusableMemory::try_from(vec).unwrap();
+		assert_eq!(rm.capacity(), capacity);
+
+		assert_eq!(ReusableMemory::try_from(vec![(); 4]), Err(ZeroSizedBaseError));
+	}
+
+	/// Tests that `truncate_returning_spare` drops the tail and returns all the unused capacity.
+	#[test]
+	fn truncate_returning_spare() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(8).unwrap());
+			let iter = (0 .. 6).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			let spare = borrow.truncate_returning_spare(2);
+			assert_eq!(spare.len(), 6);
+			assert_eq!(counter.get(), 2);
+
+			spare[0].write(DropCounter::new(&counter, 10));
+			unsafe {
+				borrow.set_len(3);
+			}
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 10]);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests removing duplicates that are not consecutive.
+	#[test]
+	fn dedup_unsorted() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(5).unwrap());
+		borrow.push_all(vec![3, 1, 3, 2, 1]).unwrap();
+
+		borrow.dedup_unsorted();
+		assert_eq!(borrow.as_slice(), &[3, 1, 2]);
+	}
+
+	/// Tests that `dedup_unsorted` drops the removed values exactly once.
+	#[test]
+	fn dedup_unsorted_drop() {
+		use std::rc::Rc;
+
+		let values = Rc::new(());
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<(String, Rc<()>)>(NonZeroUsize::new(4).unwrap());
+			let iter = ["a", "b", "a", "a"].iter().map(|&key| (key.to_string(), values.clone()));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.dedup_unsorted();
+			assert_eq!(borrow.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), &["a", "b"]);
+			assert_eq!(Rc::strong_count(&values), 3);
+		}
+
+		assert_eq!(Rc::strong_count(&values), 1);
+	}
+
+	/// Tests that borrowing again while a tracked borrow is alive panics in debug builds.
+	#[test]
+	#[should_panic(expected = "Reusable memory is already borrowed")]
+	#[cfg(debug_assertions)]
+	fn tracked_borrow_flag() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(4).unwrap());
+			std::mem::drop(borrow);
+		}
+		let borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(4).unwrap());
+		// Leaking the borrow keeps the flag set, the same as an aliasing borrow created through unsafe code.
+		std::mem::forget(borrow);
+
+		let _ = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+	}
+
+	/// Tests inserting and removing in the middle.
+	#[test]
+	fn insert_remove() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(4).unwrap());
+		borrow.push_all(vec!["a".to_string(), "c".to_string()]).unwrap();
+
+		borrow.insert(1, "b".to_string()).unwrap();
+		borrow.insert(3, "d".to_string()).unwrap();
+		assert_eq!(borrow.as_slice(), &["a", "b", "c", "d"]);
+		assert!(borrow.insert(0, "e".to_string()).is_err());
+
+		assert_eq!(borrow.remove(1), "b");
+		assert_eq!(borrow.remove(2), "d");
+		assert_eq!(borrow.as_slice(), &["a", "c"]);
+	}
+
+	/// Tests that `shift_insert` and `shift_remove` behave as `insert` and `remove`.
+	#[test]
+	fn shift_insert_remove() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(4).unwrap());
+		borrow.push_all(vec!["a".to_string(), "c".to_string()]).unwrap();
+
+		borrow.shift_insert(1, "b".to_string()).unwrap();
+		borrow.shift_insert(3, "d".to_string()).unwrap();
+		assert_eq!(borrow.as_slice(), &["a", "b", "c", "d"]);
+		assert!(borrow.shift_insert(0, "e".to_string()).is_err());
+
+		assert_eq!(borrow.shift_remove(1), "b");
+		assert_eq!(borrow.shift_remove(2), "d");
+		assert_eq!(borrow.as_slice(), &["a", "c"]);
+	}
+
+	/// Tests that removing out of bounds panics.
+	#[test]
+	#[should_panic]
+	fn remove_out_of_bounds() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(4).unwrap());
+		borrow.push(1).unwrap();
+
+		borrow.remove(1);
+	}
+
+	/// Tests draining a range and moving the tail, meant to be run under Miri with strict provenance.
+	#[test]
+	fn drain_move_tail() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<String>(NonZeroUsize::new(6).unwrap());
+		borrow.push_all((0 .. 6).map(|value| value.to_string()).collect::<Vec<_>>()).unwrap();
+
+		let mut drain = borrow.drain(1 .. 4);
+		assert_eq!(drain.next().as_deref(), Some("1"));
+		assert_eq!(drain.next_back().as_deref(), Some("3"));
+		std::mem::drop(drain);
+
+		assert_eq!(borrow.as_slice(), &["0", "4", "5"]);
+	}
+
+	/// Tests the number of `T`s that fit into the reserved memory.
+	#[test]
+	fn available_for() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(100);
+		let capacity = rm.capacity();
+
+		let available = rm.available_for::<u32>();
+		assert_eq!(available, (capacity - 3) / 4);
+		assert!(rm.needed_capacity_for::<u32>(NonZeroUsize::new(available).unwrap()) <= capacity);
+		assert!(rm.needed_capacity_for::<u32>(NonZeroUsize::new(available + 1).unwrap()) > capacity);
+
+		let _ = rm.borrow_mut_as::<u32>(NonZeroUsize::new(available).unwrap());
+		assert_eq!(rm.capacity(), capacity);
+	}
+
+	/// Tests `first_chunk` and `last_chunk` on borrows shorter than, equal to and longer than `N`.
+	#[test]
+	fn first_last_chunk() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(6).unwrap());
+
+		borrow.push_all(vec![1, 2]).unwrap();
+		assert_eq!(borrow.first_chunk::<3>(), None);
+		assert_eq!(borrow.last_chunk_mut::<3>(), None);
+
+		borrow.push(3).unwrap();
+		assert_eq!(borrow.first_chunk::<3>(), Some(&[1, 2, 3]));
+		assert_eq!(borrow.last_chunk::<3>(), Some(&[1, 2, 3]));
+
+		borrow.push_all(vec![4, 5]).unwrap();
+		assert_eq!(borrow.first_chunk::<3>(), Some(&[1, 2, 3]));
+		assert_eq!(borrow.last_chunk::<3>(), Some(&[3, 4, 5]));
+
+		borrow.first_chunk_mut::<2>().unwrap()[0] = 10;
+		borrow.last_chunk_mut::<2>().unwrap()[1] = 50;
+		assert_eq!(borrow.as_slice(), &[10, 2, 3, 4, 50]);
+	}
+
+	/// Tests that a zeroed borrow zeroes the memory past the known zero bytes, but not below them.
+	#[test]
+	fn borrow_mut_as_zeroed() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(256);
+		{
+			let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(256).unwrap());
+			borrow.push_from_exact_iter((0 .. 256).map(|_| 0xFF)).unwrap();
+		}
+		let backing_ptr = rm.backing_ptr();
+
+		// Any other borrow resets the known zero bytes, so the whole borrow is zeroed.
+		unsafe {
+			let mut borrow = rm.borrow_mut_as_zeroed::<u32>(NonZeroUsize::new(16).unwrap());
+			assert_eq!(borrow.len(), 16);
+			assert!(borrow.iter().all(|&value| value == 0));
+
+			// Breaks the contract on purpose to observe that the known zero bytes are skipped,
+			// debug builds check the contract instead.
+			if !cfg!(debug_assertions) {
+				borrow[3] = 7;
+			}
+		}
+
+		unsafe {
+			let borrow = rm.borrow_mut_as_zeroed::<u32>(NonZeroUsize::new(32).unwrap());
+			// The bytes past the known zero bytes still held `0xFF`.
+			assert!(borrow[16 ..].iter().all(|&value| value == 0));
+			assert_eq!(borrow[3], if cfg!(debug_assertions) { 0 } else { 7 });
+		}
+		// Otherwise nothing would be known to be zero.
+		assert_eq!(rm.backing_ptr(), backing_ptr);
+	}
+
+	/// Tests partially consuming `into_iter_rev` and dropping the rest.
+	#[test]
+	fn into_iter_rev() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(5).unwrap());
+			let iter = (0 .. 5).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			let mut iter = borrow.into_iter_rev();
+			assert_eq!(iter.next().map(|c| c.value), Some(4));
+			assert_eq!(iter.next().map(|c| c.value), Some(3));
+			assert_eq!(counter.get(), 3);
+
+			std::mem::drop(iter);
+			assert_eq!(counter.get(), 0);
+		}
+	}
+
+	/// Tests that borrowing multiple types returns an error for zero sized types.
+	#[test]
+	fn borrow_multiple_zero_sized() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = [NonZeroUsize::new(1).unwrap(); 2];
+
+		assert!(matches!(
+			rm.borrow_mut_two_as::<u8, ()>(capacity),
+			Err(ReusableMemoryBorrowError::ZeroSizedType)
+		));
+		assert!(matches!(
+			rm.borrow_mut_tuple_as::<((), u8)>(capacity),
+			Err(ReusableMemoryBorrowError::ZeroSizedType)
+		));
+	}
+
+	/// Tests that `truncate_counted` returns the number of dropped values.
+	#[test]
+	fn truncate_counted() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(5).unwrap());
+		borrow.push_all(vec![1, 2, 3, 4, 5]).unwrap();
+
+		assert_eq!(borrow.truncate_counted(2), 3);
+		assert_eq!(borrow.as_slice(), &[1, 2]);
+		assert_eq!(borrow.truncate_counted(4), 0);
+		assert_eq!(borrow.len(), 2);
+	}
+
+	/// Tests that `keep_first` and `keep_last` keep the right values and drop the rest once.
+	#[test]
+	fn keep_first_last() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(6).unwrap());
+			let iter = (0 .. 6).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.keep_first(4);
+			assert_eq!(counter.get(), 4);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 2, 3]);
+
+			borrow.keep_last(3);
+			assert_eq!(counter.get(), 3);
+			assert_eq!(borrow.iter().map(|c| c.value).collect::<Vec<_>>(), &[1, 2, 3]);
+
+			borrow.keep_last(5);
+			assert_eq!(borrow.len(), 3);
+		}
+
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests that `as_cow` borrows the stored values without cloning them.
+	#[test]
+	fn as_cow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u32>(NonZeroUsize::new(3).unwrap());
+		borrow.push_all(vec![1, 2, 3]).unwrap();
+
+		let cow = borrow.as_cow();
+		match cow {
+			std::borrow::Cow::Borrowed(slice) => assert_eq!(slice.as_ptr(), borrow.as_ptr()),
+			std::borrow::Cow::Owned(_) => panic!("Expected Cow::Borrowed")
+		}
+
+		let mut cow = borrow.as_cow();
+		cow.to_mut().push(4);
+		assert_eq!(&*cow, &[1, 2, 3, 4]);
+		assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+	}
+
+	/// Tests that `borrow_mut_as_prereserved` borrows the same memory as `borrow_mut_as`.
+	#[test]
+	fn borrow_mut_as_prereserved() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = NonZeroUsize::new(5).unwrap();
+		let needed_length = rm.needed_capacity_for::<u64>(capacity);
+
+		let (ptr, borrow_capacity) = {
+			let mut borrow = unsafe { rm.borrow_mut_as_prereserved::<u64>(capacity, needed_length) };
+			borrow.push(1).unwrap();
+			(borrow.as_ptr(), borrow.capacity())
+		};
+		let reserved = rm.capacity();
+
+		{
+			let borrow = rm.borrow_mut_as::<u64>(capacity);
+			assert_eq!(borrow.as_ptr(), ptr);
+			assert_eq!(borrow.capacity(), borrow_capacity);
+		}
+		assert_eq!(rm.capacity(), reserved);
+	}
+
+	/// Tests that `clear_with` passes the values back-to-front without dropping them twice.
+	#[test]
+	fn clear_with() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut cleared = Vec::new();
+		{
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(4).unwrap());
+			let iter = (0 .. 4).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.clear_with(|value| cleared.push(value));
+			assert!(borrow.is_empty());
+		}
+
+		assert_eq!(counter.get(), 4);
+		assert_eq!(cleared.iter().map(|c| c.value).collect::<Vec<_>>(), &[3, 2, 1, 0]);
+
+		std::mem::drop(cleared);
+		assert_eq!(counter.get(), 0);
+	}
+
+	/// Tests growing the first borrow of a split scratch in place.
+	#[test]
+	fn split_scratch_grow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		let mut scratch = rm.split_scratch::<u32>(NonZeroUsize::new(2).unwrap());
+		scratch.first().push_all(vec![1, 2]).unwrap();
+		assert!(scratch.first().push(3).is_err());
+
+		scratch.grow(NonZeroUsize::new(4).unwrap()).unwrap();
+		assert_eq!(scratch.first().capacity_usize(), 4);
+		scratch.first().push_all(vec![3, 4]).unwrap();
+		assert_eq!(scratch.first().as_slice(), &[1, 2, 3, 4]);
+
+		assert!(scratch.grow(NonZeroUsize::new(1000).unwrap()).is_err());
+		assert_eq!(scratch.first().capacity_usize(), 4);
+		assert!(scratch.grow(NonZeroUsize::new(usize::MAX).unwrap()).is_err());
+		assert_eq!(scratch.first().capacity_usize(), 4);
+	}
+
+	/// Tests the remainder of `tiles_exact_mut`.
+	#[test]
+	fn tiles_exact_mut() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u8>(NonZeroUsize::new(10).unwrap());
+		borrow.push_from_exact_iter(0 .. 10).unwrap();
+
+		let mut tiles = borrow.tiles_exact_mut(NonZeroUsize::new(4).unwrap());
+		for tile in &mut tiles {
+			tile[0] = 100;
+		}
+		let remainder = tiles.into_remainder();
+		assert_eq!(remainder, &[8, 9]);
+		remainder[0] = 200;
+
+		assert_eq!(borrow.as_slice(), &[100, 1, 2, 3, 100, 5, 6, 7, 200, 9]);
+	}
+
+	/// Tests that `enumerate` yields the indices with the values.
+	#[test]
+	fn enumerate() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<char>(NonZeroUsize::new(3).unwrap());
+		borrow.push_all(vec!['a', 'b', 'c']).unwrap();
+
+		assert_eq!(borrow.enumerate().collect::<Vec<_>>(), &[(0, &'a'), (1, &'b'), (2, &'c')]);
+	}
+
+	/// Tests that small borrows shrink the reserved memory after a large one.
+	#[test]
+	fn shrink_threshold() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		rm.set_shrink_threshold(0.25);
+
+		let _ = rm.borrow_mut_as::<u8>(NonZeroUsize::new(1000).unwrap());
+		assert!(rm.capacity() >= 1000);
+
+		let _ = rm.borrow_mut_as::<u8>(NonZeroUsize::new(500).unwrap());
+		assert!(rm.capacity() >= 1000);
+
+		let _ = rm.borrow_mut_as::<u8>(NonZeroUsize::new(100).unwrap());
+		assert!(rm.capacity() >= 100 && rm.capacity() < 1000);
+
+		let _ = rm.borrow_mut_as::<u8>(NonZeroUsize::new(50).unwrap());
+		assert!(rm.capacity() >= 50 && rm.capacity() < 1000);
+	}
+
+	/// Tests that the slice from `into_slice` outlives the borrow and its values are not dropped.
+	#[test]
+	fn into_slice() {
+		let counter = Cell::new(0);
+
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let slice = {
+			let mut borrow = rm.borrow_mut_as::<DropCounter>(NonZeroUsize::new(3).unwrap());
+			let iter = (0 .. 3).map(|value| DropCounter::new(&counter, value));
+			borrow.push_from_exact_iter(iter).unwrap();
+
+			borrow.into_slice()
+		};
+
+		assert_eq!(slice.iter().map(|c| c.value).collect::<Vec<_>>(), &[0, 1, 2]);
+		assert_eq!(counter.get(), 3);
+	}
+
+	/// Tests the remaining capacity returned by `push_reporting`.
+	#[test]
+	fn push_reporting() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let mut borrow = rm.borrow_mut_as::<u16>(NonZeroUsize::new(3).unwrap());
+
+		assert_eq!(borrow.push_reporting(1).unwrap(), 2);
+		assert_eq!(borrow.push_reporting(2).unwrap(), 1);
+		assert_eq!(borrow.push_reporting(3).unwrap(), 0);
+		assert!(borrow.push_reporting(4).is_err());
+		assert_eq!(borrow.as_slice(), &[1, 2, 3]);
+	}
+
+	/// Tests that an over-aligned borrow after `reserve_for_aligned` does not reallocate.
+	#[test]
+	fn reserve_for_aligned() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		rm.reserve_for_aligned::<u32>(NonZeroUsize::new(16).unwrap(), NonZeroUsize::new(64).unwrap());
+		let backing_ptr = rm.backing_ptr();
+		let capacity = rm.capacity();
+
+		let layout = std::alloc::Layout::from_size_align(16 * 4, 64).unwrap();
+		let ptr = unsafe { rm.borrow_mut_raw(layout) };
+
+		assert_eq!(ptr.as_ptr() as usize % 64, 0);
+		assert_eq!(rm.backing_ptr(), backing_ptr);
+		assert_eq!(rm.capacity(), capacity);
+	}
+
+	/// Tests that a borrow swapped in through `first` is not used to compute the spare memory.
+	#[test]
+	#[should_panic(expected = "The first borrow was swapped for a different one")]
+	fn split_scratch_swapped_first() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		let mut foreign_rm: ReusableMemory<u8> = ReusableMemory::with_capacity(1024);
+		let mut foreign = foreign_rm.borrow_mut_as::<u32>(NonZeroUsize::new(1).unwrap());
+
+		let mut scratch = rm.split_scratch::<u32>(NonZeroUsize::new(2).unwrap());
+		std::mem::swap(scratch.first(), &mut foreign);
+
+		scratch.split::<u8>();
+	}
+
+	/// Tests that `SplitScratch::grow` does not grow a borrow swapped in through `first`.
+	#[test]
+	#[should_panic(expected = "The first borrow was swapped for a different one")]
+	fn split_scratch_grow_swapped_first() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		let mut foreign_rm: ReusableMemory<u8> = ReusableMemory::with_capacity(4);
+		let mut foreign = foreign_rm.borrow_mut_as::<u32>(NonZeroUsize::new(1).unwrap());
+
+		let mut scratch = rm.split_scratch::<u32>(NonZeroUsize::new(2).unwrap());
+		std::mem::swap(scratch.first(), &mut foreign);
+
+		let _ = scratch.grow(NonZeroUsize::new(4).unwrap());
+	}
+
+	/// Tests that `borrow_mut_as_reusing` doesn't hand out a borrow when the needed bytes overflow.
+	#[test]
+	#[should_panic(expected = "capacity overflow")]
+	fn borrow_mut_as_reusing_overflow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::with_capacity(64);
+		let _ = rm.borrow_mut_as_reusing::<u32>(NonZeroUsize::new(usize::MAX / 2).unwrap());
+	}
+
+	/// Tests that a zeroed borrow panics in debug builds if the previous one was not zeroed again.
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "The previous zeroed borrow was not zeroed again")]
+	fn borrow_mut_as_zeroed_not_rezeroed() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let capacity = NonZeroUsize::new(16).unwrap();
+
+		unsafe {
+			let mut borrow = rm.borrow_mut_as_zeroed::<u32>(capacity);
+			borrow[3] = 7;
+		}
+		unsafe {
+			let _ = rm.borrow_mut_as_zeroed::<u32>(capacity);
+		}
+	}
+
+	/// Tests that `reset_tracking` clears the flag of a leaked tracked borrow.
+	#[test]
+	fn reset_tracking() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let mut borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(4).unwrap());
+			borrow.push(1).unwrap();
+		}
+		let borrow = rm.borrow_mut_as_tracked::<u32>(NonZeroUsize::new(4).unwrap());
+		std::mem::forget(borrow);
+
+		rm.reset_tracking();
+		let _ = rm.borrow_mut_as::<u32>(NonZeroUsize::new(4).unwrap());
+		assert_eq!(rm.high_water_bytes(), 4);
+	}
+
+	/// Tests that the layout of multiple borrows panics instead of wrapping around.
+	#[test]
+	#[should_panic(expected = "capacity overflow")]
+	fn needed_capacity_for_two_overflow() {
+		let rm: ReusableMemory<u8> = ReusableMemory::new();
+		let _ = rm.needed_capacity_for_two::<u64, u8>([
+			NonZeroUsize::new(usize::MAX / 8 + 2).unwrap(),
+			NonZeroUsize::new(1).unwrap()
+		]);
+	}
+
+	/// Tests that borrowing multiple types panics instead of handing out too large borrows.
+	#[test]
+	#[should_panic(expected = "capacity overflow")]
+	fn borrow_mut_two_as_overflow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		let _ = rm.borrow_mut_two_as::<u64, u8>([
+			NonZeroUsize::new(usize::MAX / 8 + 2).unwrap(),
+			NonZeroUsize::new(1).unwrap()
+		]);
+	}
+
+	/// Tests that `reserve_for_aligned` panics instead of reserving too little memory.
+	#[test]
+	#[should_panic(expected = "capacity overflow")]
+	fn reserve_for_aligned_overflow() {
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		rm.reserve_for_aligned::<u64>(
+			NonZeroUsize::new(usize::MAX / 8 + 2).unwrap(),
+			NonZeroUsize::new(8).unwrap()
+		);
+	}
+
+	/// Tests that values written through a raw borrow are never dropped.
+	#[test]
+	fn borrow_raw_no_drop() {
+		let counter = Cell::new(0);
+		let mut rm: ReusableMemory<u8> = ReusableMemory::new();
+		{
+			let raw = rm.borrow_raw_mut_as::<DropCounter>(NonZeroUsize::new(3).unwrap());
+			for (value, slot) in raw.iter_mut().enumerate() {
+				*slot = std::mem::MaybeUninit::new(DropCounter::new(&counter, value as u8));
+			}
+		}
+		rm.release();
+
+		// All three values are still alive, none of them was dropped.
+		assert_eq!(counter.get(), 3);
+	}
 }
@@ -0,0 +1,74 @@
+use std::{
+	mem,
+	ops::{Deref, DerefMut},
+	sync::{Mutex, MutexGuard, PoisonError}
+};
+
+use super::ReusableMemory;
+
+/// Pool of reusable memories that can be shared between threads.
+///
+/// Memories acquired from the pool are returned to it when the guard is dropped,
+/// so their reserved memory is reused by the next `acquire`.
+#[derive(Debug)]
+pub struct ReusableMemoryPool<B = u8> {
+	memories: Mutex<Vec<ReusableMemory<B>>>
+}
+impl<B> ReusableMemoryPool<B> {
+	/// Creates an empty pool.
+	///
+	/// Panics if `size_of::<B>() == 0`
+	pub fn new() -> Self { Self::with_capacity(0, 0) }
+
+	/// Creates a pool of `count` memories, each with capacity `each_capacity` counted in `B`s.
+	///
+	/// Panics if `size_of::<B>() == 0`
+	pub fn with_capacity(count: usize, each_capacity: usize) -> Self {
+		assert_ne!(mem::size_of::<B>(), 0);
+
+		let memories = (0 .. count).map(|_| ReusableMemory::with_capacity(each_capacity)).collect();
+		ReusableMemoryPool { memories: Mutex::new(memories) }
+	}
+
+	/// Returns the number of memories currently in the pool.
+	pub fn available(&self) -> usize { self.lock().len() }
+
+	/// Takes a memory from the pool, or creates a new one if the pool is empty.
+	pub fn acquire(&self) -> PoolGuard<'_, B> {
+		let memory = self.lock().pop().unwrap_or_default();
+
+		PoolGuard { pool: self, memory }
+	}
+
+	/// Locks the memories in the pool.
+	///
+	/// A panic while the lock is held cannot leave the memories in an invalid state,
+	/// so a poisoned lock is used as is.
+	fn lock(&self) -> MutexGuard<'_, Vec<ReusableMemory<B>>> {
+		self.memories.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+impl<B> Default for ReusableMemoryPool<B> {
+	fn default() -> Self { Self::new() }
+}
+
+/// Memory acquired from `ReusableMemoryPool`, returned to the pool on drop.
+#[derive(Debug)]
+pub struct PoolGuard<'pool, B> {
+	pool: &'pool ReusableMemoryPool<B>,
+	memory: ReusableMemory<B>
+}
+impl<B> Deref for PoolGuard<'_, B> {
+	type Target = ReusableMemory<B>;
+
+	fn deref(&self) -> &Self::Target { &self.memory }
+}
+impl<B> DerefMut for PoolGuard<'_, B> {
+	fn deref_mut(&mut self) -> &mut Self::Target { &mut self.memory }
+}
+impl<B> Drop for PoolGuard<'_, B> {
+	fn drop(&mut self) {
+		let memory = mem::take(&mut self.memory);
+		self.pool.lock().push(memory);
+	}
+}
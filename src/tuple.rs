@@ -0,0 +1,115 @@
+use std::{mem, num::NonZeroUsize, ptr};
+
+use super::{base::align_up, borrow::ReusableMemoryBorrow};
+
+/// List of types that can be borrowed together by `ReusableMemory::borrow_mut_tuple_as`.
+///
+/// This is implemented for tuples of up to 8 types, for example `(u64, u32, u8)`.
+///
+/// ### Safety
+///
+/// * `LAYOUTS` must contain `(size_of, align_of)` of each type in the list, in order.
+/// * `borrows_from_raw` must create the borrows in order, each one starting at the first offset
+///   aligned for its type after the end of the capacity of the previous one.
+pub unsafe trait BorrowLayoutList {
+	/// Capacities of the borrows, one for each type in the list.
+	type Caps: AsRef<[NonZeroUsize]> + Copy;
+	/// The borrows returned from `ReusableMemory::borrow_mut_tuple_as`.
+	type Borrows<'mem>
+	where
+		Self: 'mem;
+
+	/// `(size_of, align_of)` of each type in the list.
+	const LAYOUTS: &'static [(usize, usize)];
+
+	/// Constructs the borrows from a pointer to the start of the memory.
+	///
+	/// ### Safety
+	///
+	/// * `base` must be aligned to the maximum alignment in `LAYOUTS`.
+	/// * `base` must be a valid pointer into enough memory for all the borrows laid out this way.
+	unsafe fn borrows_from_raw<'mem>(base: *mut u8, caps: Self::Caps) -> Self::Borrows<'mem>
+	where
+		Self: 'mem;
+}
+
+/// Returns the byte offset of each block relative to the start of the memory.
+///
+/// Panics with "capacity overflow" if the offsets do not fit into `usize`.
+pub(crate) fn block_offsets<'a>(
+	layouts: &'a [(usize, usize)], caps: &'a [NonZeroUsize]
+) -> impl Iterator<Item = usize> + 'a {
+	layouts.iter().zip(caps.iter()).scan(0, |needed_bytes, (&(size, align), capacity)| {
+		// where the block starts, in bytes
+		let start = align_up(*needed_bytes, align);
+		assert!(start >= *needed_bytes, "capacity overflow");
+		// where the block ends
+		*needed_bytes = block_end(start, size, *capacity);
+
+		Some(start)
+	})
+}
+
+/// Returns the number of bytes from the start of the first block to the end of the last one.
+///
+/// Panics with "capacity overflow" if the number does not fit into `usize`.
+pub(crate) fn list_needed_bytes(layouts: &[(usize, usize)], caps: &[NonZeroUsize]) -> usize {
+	match block_offsets(layouts, caps).zip(layouts.iter().zip(caps.iter())).last() {
+		Some((start, (&(size, _), &capacity))) => block_end(start, size, capacity),
+		None => 0
+	}
+}
+
+/// Returns where a block of `capacity` values of `size` bytes starting at `start` ends.
+fn block_end(start: usize, size: usize, capacity: NonZeroUsize) -> usize {
+	size.checked_mul(capacity.get())
+		.and_then(|bytes| start.checked_add(bytes))
+		.expect("capacity overflow")
+}
+
+/// Returns the maximum alignment in `layouts`.
+pub(crate) fn list_align(layouts: &[(usize, usize)]) -> usize {
+	layouts.iter().map(|&(_, align)| align).max().unwrap_or(1)
+}
+
+macro_rules! impl_borrow_layout_list {
+	(
+		$count: literal; $($gen_name: ident),+
+	) => {
+		unsafe impl<$($gen_name),+> BorrowLayoutList for ($($gen_name,)+) {
+			type Caps = [NonZeroUsize; $count];
+			type Borrows<'mem> = ($(ReusableMemoryBorrow<'mem, $gen_name>,)+) where Self: 'mem;
+
+			const LAYOUTS: &'static [(usize, usize)] = &[$((mem::size_of::<$gen_name>(), mem::align_of::<$gen_name>())),+];
+
+			unsafe fn borrows_from_raw<'mem>(base: *mut u8, caps: Self::Caps) -> Self::Borrows<'mem>
+			where
+				Self: 'mem
+			{
+				let mut blocks = block_offsets(Self::LAYOUTS, &caps).zip(caps.iter());
+
+				(
+					$(
+						{
+							let (offset, &capacity) = blocks.next().unwrap();
+
+							ReusableMemoryBorrow::<$gen_name>::from_raw_parts(
+								ptr::NonNull::new_unchecked(base.add(offset) as *mut $gen_name),
+								capacity
+							)
+						},
+					)+
+				)
+			}
+		}
+	}
+}
+
+impl_borrow_layout_list!(1; A);
+impl_borrow_layout_list!(2; A, B);
+impl_borrow_layout_list!(3; A, B, C);
+impl_borrow_layout_list!(4; A, B, C, D);
+impl_borrow_layout_list!(5; A, B, C, D, E);
+impl_borrow_layout_list!(6; A, B, C, D, E, F);
+impl_borrow_layout_list!(7; A, B, C, D, E, F, G);
+impl_borrow_layout_list!(8; A, B, C, D, E, F, G, H);